@@ -0,0 +1,235 @@
+// Expected answers, so a run can be checked against known results.
+//
+// The file is a small subset of TOML: one [section] per input set, holding
+// dayN.partM keys whose values are integers or double quoted strings.
+//
+//   # Answers for the inputs in data_aoc2022/
+//   [data_aoc2022]
+//   day1.part1 = 71780
+//   day5.part1 = "SHMSDGZVC"
+//   day10.part2 = "ERCREPCJ"
+//
+// Strings understand the escapes \n, \t, \" and \\.  Answers are compared by
+// how they print, so 5 matches Number(5), Signed(5) or Big(5), and a string
+// of '#'s and '.'s matches the Grid it draws.  The runner reads letters drawn
+// by a day (see ocr.rs) before checking, so those are stored as text.
+
+use crate::day::Answer;
+use crate::error::AocError;
+use crate::input::{Input, Line};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::HashMap;
+use std::fmt;
+
+pub struct Answers {
+    sets: HashMap<String, HashMap<(usize, usize), Answer>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Verdict {
+    Pass,
+    Fail,
+    Unknown,    // no expected answer recorded
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Verdict::Pass => write!(f, "pass"),
+            Verdict::Fail => write!(f, "fail"),
+            Verdict::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+impl Answers {
+    pub fn load(filename: &str) -> Result<Answers, AocError> {
+        Answers::parse(&Input::open(filename)?)
+    }
+
+    pub fn parse(input: &Input) -> Result<Answers, AocError> {
+        lazy_static! {
+            static ref SECTION_RE: Regex =
+                Regex::new(r"^\s*\[\s*([A-Za-z0-9_.-]+)\s*\]\s*(#.*)?$").unwrap();
+            static ref ENTRY_RE: Regex =
+                Regex::new(r"^\s*day([0-9]+)\.part([12])\s*=\s*(.*)$").unwrap();
+        }
+
+        let mut sets: HashMap<String, HashMap<(usize, usize), Answer>> = HashMap::new();
+        let mut current: Option<String> = None;
+
+        for line in input.lines() {
+            let trimmed = line.text.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            if let Some(caps) = SECTION_RE.captures(line.text) {
+                let name = caps[1].to_string();
+                sets.entry(name.clone()).or_default();
+                current = Some(name);
+            }
+            else if let Some(caps) = ENTRY_RE.captures(line.text) {
+                let day: usize = line.field(&caps, 1)?;
+                let part: usize = line.field(&caps, 2)?;
+                let set = match &current {
+                    Some(name) => sets.get_mut(name).unwrap(),
+                    None => return Err(line.error(1, "answer given before any [input-set] section")),
+                };
+                let value = caps.get(3).unwrap();
+                let answer = parse_value(&line, value.start() + 1, value.as_str())?;
+                if set.insert((day, part), answer).is_some() {
+                    return Err(line.error(1, &format!("duplicate answer for day {} part {}", day, part)));
+                }
+            }
+            else {
+                return Err(line.error(1, &format!("unrecognized line '{}'", line.text)));
+            }
+        }
+
+        Ok(Answers { sets })
+    }
+
+    pub fn expected(&self, set: &str, day: usize, part: usize) -> Option<&Answer> {
+        self.sets.get(set).and_then(|answers| answers.get(&(day, part)))
+    }
+
+    pub fn verify(&self, set: &str, day: usize, part: usize, answer: &Answer) -> Verdict {
+        match self.expected(set, day, part) {
+            None => Verdict::Unknown,
+            Some(expected) if same(expected, answer) => Verdict::Pass,
+            Some(_) => Verdict::Fail,
+        }
+    }
+}
+
+fn same(expected: &Answer, answer: &Answer) -> bool {
+    match (expected, answer.value()) {
+        (Answer::None, _) | (_, Answer::None) => expected == answer.value(),
+        _ => expected.to_string() == answer.to_string(),
+    }
+}
+
+// Parse the value of an entry, which starts at the given column.
+fn parse_value(line: &Line, column: usize, text: &str) -> Result<Answer, AocError> {
+    let text = text.trim_end();
+
+    if let Some(quoted) = text.strip_prefix('"') {
+        let mut s = String::new();
+        let mut chars = quoted.char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    let after = &quoted[i + 1..];
+                    let rest = after.trim_start();
+                    if !rest.is_empty() && !rest.starts_with('#') {
+                        let rest_column = column + i + 2 + after.len() - rest.len();
+                        return Err(line.error(rest_column, "unexpected text after string"));
+                    }
+                    return Ok(Answer::Message(s));
+                }
+                '\\' => {
+                    match chars.next() {
+                        Some((_, 'n')) => s.push('\n'),
+                        Some((_, 't')) => s.push('\t'),
+                        Some((_, '"')) => s.push('"'),
+                        Some((_, '\\')) => s.push('\\'),
+                        _ => return Err(line.error(column + i + 1, "invalid escape in string")),
+                    }
+                }
+                c => s.push(c),
+            }
+        }
+        Err(line.error(column, "unterminated string"))
+    }
+    else {
+        // Integers, with TOML's optional underscores, and an optional comment.
+        // Use the narrowest variant that holds the value.
+        let number = match text.split_once('#') {
+            Some((number, _comment)) => number.trim_end(),
+            None => text,
+        };
+        let digits = number.replace('_', "");
+        if let Ok(n) = digits.parse::<usize>() {
+            Ok(Answer::Number(n))
+        }
+        else if let Ok(n) = digits.parse::<i64>() {
+            Ok(Answer::Signed(n))
+        }
+        else if let Ok(n) = digits.parse::<i128>() {
+            Ok(Answer::Big(n))
+        }
+        else {
+            Err(line.error(column, &format!("invalid answer '{}'", number)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ANSWERS: &str = "\
+# A comment
+[alice]
+day1.part1 = 71780
+day1.part2 = 212_489   # with a comment
+day5.part1 = \"SHMSDGZVC\"
+day10.part2 = \"#.\\n.#\\n\"
+
+day15.part2 = -12
+day16.part1 = 100_000_000_000_000_000_000
+
+[bob]
+day1.part1 = 1
+";
+
+    #[test]
+    fn test_parse() {
+        let answers = Answers::parse(&Input::from_text(ANSWERS)).unwrap();
+        assert_eq!(answers.expected("alice", 1, 1), Some(&Answer::Number(71780)));
+        assert_eq!(answers.expected("alice", 1, 2), Some(&Answer::Number(212489)));
+        assert_eq!(answers.expected("alice", 5, 1), Some(&Answer::Message("SHMSDGZVC".to_string())));
+        assert_eq!(answers.expected("alice", 10, 2), Some(&Answer::Message("#.\n.#\n".to_string())));
+        assert_eq!(answers.expected("alice", 15, 2), Some(&Answer::Signed(-12)));
+        assert_eq!(answers.expected("alice", 16, 1), Some(&Answer::Big(100_000_000_000_000_000_000)));
+        assert_eq!(answers.expected("bob", 1, 1), Some(&Answer::Number(1)));
+        assert_eq!(answers.expected("bob", 1, 2), None);
+        assert_eq!(answers.expected("carol", 1, 1), None);
+    }
+
+    #[test]
+    fn test_verify() {
+        let answers = Answers::parse(&Input::from_text(ANSWERS)).unwrap();
+        assert_eq!(answers.verify("alice", 1, 1, &Answer::Number(71780)), Verdict::Pass);
+        assert_eq!(answers.verify("bob", 1, 1, &Answer::Number(71780)), Verdict::Fail);
+        assert_eq!(answers.verify("bob", 5, 1, &Answer::Number(71780)), Verdict::Unknown);
+        assert_eq!(answers.verify("alice", 5, 1, &Answer::None), Verdict::Fail);
+        assert_eq!(answers.verify("alice", 1, 1, &Answer::Big(71780)), Verdict::Pass);
+        assert_eq!(answers.verify("alice", 15, 2, &Answer::Signed(-12)), Verdict::Pass);
+        let grid = Answer::Grid { width: 2, height: 2, cells: vec![true, false, false, true] };
+        assert_eq!(answers.verify("alice", 10, 2, &grid), Verdict::Pass);
+        let detailed = Answer::Detailed { answer: Box::new(Answer::Number(71780)), detail: "how".to_string() };
+        assert_eq!(answers.verify("alice", 1, 1, &detailed), Verdict::Pass);
+        let detailed = Answer::Detailed { answer: Box::new(Answer::None), detail: "how".to_string() };
+        assert_eq!(answers.verify("bob", 1, 1, &detailed), Verdict::Fail);
+    }
+
+    #[test]
+    fn test_errors() {
+        let position = |text: &str| {
+            match Answers::parse(&Input::from_text(text)) {
+                Err(AocError::Parse { line, column, .. }) => (line, column),
+                _ => panic!("expected a parse error for {:?}", text),
+            }
+        };
+        assert_eq!(position("day1.part1 = 5"), (1, 1));
+        assert_eq!(position("[a]\nday1.part1 = x"), (2, 14));
+        assert_eq!(position("[a]\nday1.part1 = \"abc"), (2, 14));
+        assert_eq!(position("[a]\nday1.part1 = \"a\\qc\""), (2, 16));
+        assert_eq!(position("[a]\nday1.part1 = \"a\" b"), (2, 18));
+        assert_eq!(position("[a]\nday1.part1 = 1\nday1.part1 = 2"), (3, 1));
+        assert_eq!(position("[a]\nday1.part3 = 1"), (2, 1));
+    }
+}
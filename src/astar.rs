@@ -1,113 +1,121 @@
-
-use std::{hash::Hash, marker::PhantomData, collections::HashMap};
+use std::collections::HashMap;
+use std::hash::Hash;
 use priority_queue::PriorityQueue;
-use std::fmt::Debug;
-
-pub trait AStarState<K> {
-    fn is_final(&self) -> bool;
-    fn next_states(&self) -> Vec<Box<Self>>;
-    fn cost(&self) -> isize;
-    fn completion_estimate(&self) -> isize;
-    fn get_key(&self) -> K;
-    fn get_key_metric(&self) -> usize;
-}
+use crate::observer::{Event, Monitor, Stats};
 
-pub struct AStarSearch<T, K>     
-where T: AStarState<K>+Hash+Eq+Debug {
-    minimize: bool,
-    in_progress: PriorityQueue<T, isize>,
-    metrics: HashMap<K, usize>,
-    verbose: bool,
-    _marker: PhantomData<K>,
-}
+// A generic best-first (A*) search.
+//
+// A Problem describes the states, how to move between them and at what cost,
+// and which states are goals.  The search finds the cheapest route from any
+// start state to a goal.  (Maximizing problems go to branch_bound.)
+//
+// With a heuristic of zero this is Dijkstra's algorithm, and with unit costs
+// as well it is a breadth first search.  For the result to be optimal the
+// heuristic must never overestimate the remaining cost.
+
+pub trait Problem {
+    type State: Clone + Eq + Hash;
 
-impl<T, K> AStarSearch<T, K>  
-where T: AStarState<K>+Hash+Eq+Debug,
-      K: Hash+Eq
-{
-    pub fn new(min: bool, verbose: bool) -> AStarSearch<T, K> {
-        let minimize = min;  // TODO: support maximizing, too.
+    // Where the search starts.  Several starts are searched from at once.
+    fn starts(&self) -> Vec<Self::State>;
 
-        let in_progress: PriorityQueue<T, isize> = PriorityQueue::new();
-        let metrics: HashMap<K, usize> = HashMap::new();
+    // The states reachable in one step from state, with the cost of each step.
+    fn successors(&self, state: &Self::State) -> Vec<(Self::State, isize)>;
 
-        AStarSearch {minimize, in_progress: in_progress, metrics, verbose, _marker: PhantomData}
+    // Estimate of the cost from state to the nearest goal.
+    fn heuristic(&self, _state: &Self::State) -> isize {
+        0
     }
 
-    fn push(&mut self, s: T, priority: isize) {
-        // Extract state-key, metric
-        let k = s.get_key();
-        let metric = s.get_key_metric();
+    fn is_goal(&self, state: &Self::State) -> bool;
+}
 
-        // If there's already a metric associated with this state-key
-        if self.metrics.contains_key(&k) {
-            // If this metric is better
-            if metric > *self.metrics.get(k).unwrap() {
-                // Update the metric for this state-key
-                self.metrics.insert(k, metric);
-            }
-        }
-        else {
-            // First time seeing this key, store it's metric
-            self.metrics.insert(k, metric);
-        }
+// The route found to a goal.  path runs from a start to the goal, inclusive.
+#[derive(Debug, PartialEq)]
+pub struct Found<S> {
+    pub cost: isize,
+    pub path: Vec<S>,
+}
 
-        if self.minimize {
-            // Priority queue returns the largest (most postitive) priority
-            // first.  When minimizing, negate the priority to reverse this.
-            // println!("Pushing {:?}, {}", s, -priority);
-            self.in_progress.push(s, -priority);
-        }
-        else {
-            // When maximizing, 
-            // println!("Pushing {:?}, {}", s, priority);
-            self.in_progress.push(s, priority);
-        }
+impl<S> Found<S> {
+    // Number of steps taken, which is the cost when every step costs 1.
+    pub fn steps(&self) -> usize {
+        self.path.len() - 1
     }
+}
 
-    pub fn set_start(&mut self, s: T) {
-        let cost = s.cost();
-        let heuristic = s.completion_estimate();
-        let priority = cost + heuristic;
-        self.push(s, priority);
+pub struct AStarSearch<'a, P: Problem> {
+    problem: &'a P,
+    monitor: Monitor,
+}
+
+impl<'a, P: Problem> AStarSearch<'a, P> {
+    pub fn new(problem: &'a P) -> AStarSearch<'a, P> {
+        AStarSearch { problem, monitor: Monitor::new("astar") }
     }
 
-    pub fn search(&mut self) -> Option<T> {
-        let mut retval = None;
+    // What the search did, for tuning problems and heuristics.  Generated
+    // counts every push, including ones that improve on a queued state.
+    pub fn stats(&self) -> &Stats {
+        self.monitor.stats()
+    }
 
-        // while in_progress is not empty
-        while self.in_progress.len() > 0 {
-            // remove highest priority solution in progress
-            let (state, _priority) = self.in_progress.pop().unwrap();
-            if self.verbose {
-                println!("{} Popped {}. {:?}", self.in_progress.len(), _priority, state);
-                // println!("Queue: {}", self.in_progress.len());
-            }
+    // The queue pops the largest priority first, so negate the estimate.
+    fn priority(&self, state: &P::State, cost: isize) -> isize {
+        -(cost + self.problem.heuristic(state))
+    }
+
+    pub fn search(&mut self) -> Option<Found<P::State>> {
+        let mut queue: PriorityQueue<P::State, isize> = PriorityQueue::new();
+        let mut best: HashMap<P::State, isize> = HashMap::new();   // best cost found to each state
+        let mut came_from: HashMap<P::State, P::State> = HashMap::new();
+
+        for start in self.problem.starts() {
+            best.insert(start.clone(), 0);
+            queue.push(start.clone(), self.priority(&start, 0));
+            self.monitor.record(Event::Generated, queue.len());
+        }
 
-            if state.is_final() {
-                // We found the final state!
-                retval = Some(state);
-                break;
+        while let Some((state, _priority)) = queue.pop() {
+            let cost = best[&state];
+            if self.problem.is_goal(&state) {
+                self.monitor.record(Event::Improved(cost), queue.len());
+                self.monitor.record(Event::Finished, queue.len());
+                return Some(Found { cost, path: Self::route(&came_from, state) });
             }
 
-            // Extract state-key and evaluate metric
-            let k = state.get_key();
-            let metric = state.get_key_metric();
-
-            // Don't explore this state if one with the same key and better metric has been seen.
-            if metric >= self.metrics[k] {
-                // Explore
-                // For each next state from this one, evaluate it and add it back
-                // to the priority queue if it's worth exploring.
-                for next in state.next_states() {
-                    let cost = next.cost();
-                    let heuristic = next.completion_estimate();
-                    self.push(*next, cost + heuristic);
+            self.monitor.record(Event::Expanded, queue.len());
+            for (next, step_cost) in self.problem.successors(&state) {
+                let next_cost = cost + step_cost;
+                if let Some(known) = best.get(&next) {
+                    if next_cost >= *known {
+                        // We've already been here at least as well.
+                        continue;
+                    }
                 }
+
+                // Pushing a state that is already queued just updates its priority.
+                best.insert(next.clone(), next_cost);
+                came_from.insert(next.clone(), state.clone());
+                let priority = self.priority(&next, next_cost);
+                queue.push(next, priority);
+                self.monitor.record(Event::Generated, queue.len());
             }
         }
 
-        retval
+        self.monitor.record(Event::Finished, 0);
+        None
+    }
+
+    // Follow came_from back from the goal to a start.
+    fn route(came_from: &HashMap<P::State, P::State>, goal: P::State) -> Vec<P::State> {
+        let mut path = vec![goal];
+        while let Some(previous) = came_from.get(path.last().unwrap()) {
+            path.push(previous.clone());
+        }
+        path.reverse();
+
+        path
     }
 }
 
@@ -115,62 +123,137 @@ where T: AStarState<K>+Hash+Eq+Debug,
 mod tests {
     use super::*;
 
-    #[derive(Hash)]
-    struct TestStateKey {
-        position: isize,
+    // Walk along a line, one step at a time, from start to goal.
+    struct Line {
+        start: isize,
+        goal: isize,
     }
 
-    #[derive(Hash, PartialEq, Eq, Debug)]
-    struct TestState {
-        moves: usize,
-        position: isize,
-    }
+    impl Problem for Line {
+        type State = isize;
 
-    impl Drop for TestState {
-        fn drop(&mut self) {
-            println!("Dropping {:?}", &self);
+        fn starts(&self) -> Vec<isize> {
+            vec![self.start]
+        }
+
+        fn successors(&self, position: &isize) -> Vec<(isize, isize)> {
+            vec![(position + 1, 1), (position - 1, 1)]
         }
-    }
 
-    impl AStarState<TestStateKey> for TestState {
-        fn is_final(&self) -> bool {
-            self.position >= 10
+        fn heuristic(&self, position: &isize) -> isize {
+            (self.goal - position).abs()
         }
 
-        fn next_states(&self) -> Vec<Box<TestState>>
-        {
-            println!("next_states from n:{}", self.position);
+        fn is_goal(&self, position: &isize) -> bool {
+            *position == self.goal
+        }
+    }
 
-            let mut v = Vec::new();
+    // A small maze.  Moving costs 1, or 5 onto a '~'.  There may be several starts.
+    struct Maze {
+        rows: Vec<Vec<char>>,
+    }
 
-            v.push(Box::new(TestState{moves: self.moves+1, position: self.position+1}));
-            v.push(Box::new(TestState{moves: self.moves+1, position: self.position-1}));
+    impl Maze {
+        fn new(text: &str) -> Maze {
+            Maze { rows: text.lines().map(|l| l.chars().collect()).collect() }
+        }
 
-            v
+        fn find(&self, c: char) -> Vec<(usize, usize)> {
+            let mut found = Vec::new();
+            for (y, row) in self.rows.iter().enumerate() {
+                for (x, cell) in row.iter().enumerate() {
+                    if *cell == c {
+                        found.push((x, y));
+                    }
+                }
+            }
+            found
         }
+    }
+
+    impl Problem for Maze {
+        type State = (usize, usize);
 
-        fn cost(&self) -> isize {
-            self.moves as isize
+        fn starts(&self) -> Vec<(usize, usize)> {
+            self.find('S')
         }
 
-        fn completion_estimate(&self) -> isize {
-            9 - self.position
+        fn successors(&self, &(x, y): &(usize, usize)) -> Vec<((usize, usize), isize)> {
+            let mut next = Vec::new();
+            for (dx, dy) in [(0, 1), (1, 0), (0, -1), (-1, 0)] {
+                let nx = x as isize + dx;
+                let ny = y as isize + dy;
+                if nx < 0 || ny < 0 || ny as usize >= self.rows.len() || nx as usize >= self.rows[0].len() {
+                    continue;
+                }
+                let (nx, ny) = (nx as usize, ny as usize);
+                match self.rows[ny][nx] {
+                    '#' => {}
+                    '~' => next.push(((nx, ny), 5)),
+                    _ => next.push(((nx, ny), 1)),
+                }
+            }
+            next
         }
 
-        fn get_key(&self) -> TestStateKey {
-            TestStateKey {position: self.position}
+        fn is_goal(&self, position: &(usize, usize)) -> bool {
+            self.rows[position.1][position.0] == 'E'
         }
+    }
+
+    #[test]
+    fn test_line() {
+        let problem = Line { start: 3, goal: 10 };
+        let mut search = AStarSearch::new(&problem);
+        let found = search.search().unwrap();
+        assert_eq!(found.cost, 7);
+        assert_eq!(found.path, (3..=10).collect::<Vec<isize>>());
+        assert_eq!(found.path.last(), Some(&10));
 
-        fn get_key_metric(&self) -> usize {
-            self.position
+        // The heuristic is exact, so nothing off the route is expanded.
+        let stats = search.stats();
+        assert_eq!(stats.expanded, 7);
+        assert_eq!(stats.generated, 9);
+        assert_eq!(stats.max_queue, 2);
+        assert_eq!(stats.improvements.len(), 1);
+        assert_eq!(stats.improvements[0].score, 7);
+    }
+
+    #[test]
+    fn test_maze() {
+        let maze = Maze::new(
+            "S.#....\n\
+             .~#.##.\n\
+             .~..#E.\n\
+             .#.....\n");
+        let mut search = AStarSearch::new(&maze);
+        let found = search.search().unwrap();
+        assert_eq!(found.cost, 13);
+        assert_eq!(found.steps(), 9);
+        assert_eq!(found.path[0], (0, 0));
+        assert_eq!(found.path.last(), Some(&(5, 2)));
+        for pair in found.path.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            assert_eq!(a.0.abs_diff(b.0) + a.1.abs_diff(b.1), 1);
         }
+        assert!(search.stats().expanded > 0);
+        assert!(search.stats().generated >= search.stats().expanded);
+    }
+
+    #[test]
+    fn test_multiple_starts() {
+        let maze = Maze::new("S....E.S");
+        let found = AStarSearch::new(&maze).search().unwrap();
+        assert_eq!(found.cost, 2);
+        assert_eq!(found.path[0], (7, 0));
     }
 
     #[test]
-    fn test_new_search() {
-        let mut search: AStarSearch<TestState, TestStateKey> = AStarSearch::new(true, false);
-        search.set_start(TestState { moves: 0, position: 3});
-        let final_state  = search.search();
-        assert_eq!(final_state, Some(TestState{moves: 7, position: 10}));
+    fn test_unreachable() {
+        let maze = Maze::new("S.#.E");
+        let mut search = AStarSearch::new(&maze);
+        assert_eq!(search.search(), None);
+        assert_eq!(search.stats().expanded, 2);
     }
 }
@@ -0,0 +1,189 @@
+use std::hash::Hash;
+use priority_queue::PriorityQueue;
+use crate::observer::{Event, Monitor, Stats};
+
+// A generic branch and bound solver for maximization problems.
+//
+// Partial solutions are explored most promising first, by their upper bound.
+// A partial solution whose upper bound can't beat the best score found so
+// far is discarded, along with everything that could be built from it, and
+// each time a new best is found the queue is pruned of such solutions.
+//
+// The upper bound must never be less than the score of any solution that can
+// be reached from a state, or the optimum may be pruned away.
+
+pub trait Problem {
+    type State: Clone + Eq + Hash;
+
+    // The empty solution everything else is built from.
+    fn start(&self) -> Self::State;
+
+    // Value of the solution so far.
+    fn score(&self, state: &Self::State) -> usize;
+
+    // No solution reachable from state scores more than this.
+    fn upper_bound(&self, state: &Self::State) -> usize;
+
+    // A complete solution has no successors worth looking at.
+    fn is_complete(&self, state: &Self::State) -> bool;
+
+    // Partial solutions extending state by one decision.
+    fn successors(&self, state: &Self::State) -> Vec<Self::State>;
+}
+
+pub struct BranchAndBound<'a, P: Problem> {
+    problem: &'a P,
+    monitor: Monitor,
+}
+
+impl<'a, P: Problem> BranchAndBound<'a, P> {
+    pub fn new(problem: &'a P) -> BranchAndBound<'a, P> {
+        BranchAndBound { problem, monitor: Monitor::new("branch_bound") }
+    }
+
+    // What the solver did, for tuning bounds.  Pruned counts states discarded
+    // because of their upper bound, whether or not they were ever queued.
+    pub fn stats(&self) -> &Stats {
+        self.monitor.stats()
+    }
+
+    // Find the highest scoring solution.
+    pub fn solve(&mut self) -> P::State {
+        let problem = self.problem;
+        let mut queue: PriorityQueue<P::State, usize> = PriorityQueue::new();
+
+        let start = problem.start();
+        let mut best = start.clone();
+        let mut best_score = problem.score(&best);
+        queue.push(start.clone(), problem.upper_bound(&start));
+        self.monitor.record(Event::Generated, queue.len());
+
+        while let Some((state, bound)) = queue.pop() {
+            // The bound may have been good enough when this was pushed, but not now.
+            if bound <= best_score {
+                self.monitor.record(Event::Pruned(1), queue.len());
+                continue;
+            }
+
+            let score = problem.score(&state);
+            if score > best_score {
+                best = state.clone();
+                best_score = score;
+
+                // Drop everything that can no longer beat the new best.
+                let before = queue.len();
+                queue = queue.into_iter().filter(|(_, bound)| *bound > best_score).collect();
+                self.monitor.record(Event::Pruned(before - queue.len()), queue.len());
+                self.monitor.record(Event::Improved(score as isize), queue.len());
+            }
+
+            if problem.is_complete(&state) {
+                continue;
+            }
+
+            self.monitor.record(Event::Expanded, queue.len());
+            for next in problem.successors(&state) {
+                let bound = problem.upper_bound(&next);
+                if bound <= best_score {
+                    self.monitor.record(Event::Pruned(1), queue.len());
+                    continue;
+                }
+                queue.push(next, bound);
+                self.monitor.record(Event::Generated, queue.len());
+            }
+        }
+
+        self.monitor.record(Event::Finished, 0);
+        best
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::random_cases;
+
+    // 0/1 knapsack: choose items, in order, to maximize value within a weight limit.
+    struct Knapsack {
+        items: Vec<(usize, usize)>,     // (weight, value)
+        capacity: usize,
+    }
+
+    // (number of items decided, chosen items as a bit mask, weight, value)
+    type Packing = (usize, u32, usize, usize);
+
+    impl Problem for Knapsack {
+        type State = Packing;
+
+        fn start(&self) -> Packing {
+            (0, 0, 0, 0)
+        }
+
+        fn score(&self, state: &Packing) -> usize {
+            state.3
+        }
+
+        // Everything still undecided that would fit on its own.
+        fn upper_bound(&self, &(decided, _, weight, value): &Packing) -> usize {
+            value + self.items[decided..].iter()
+                .filter(|(w, _)| weight + w <= self.capacity)
+                .map(|(_, v)| v)
+                .sum::<usize>()
+        }
+
+        fn is_complete(&self, state: &Packing) -> bool {
+            state.0 == self.items.len()
+        }
+
+        fn successors(&self, &(decided, chosen, weight, value): &Packing) -> Vec<Packing> {
+            let (w, v) = self.items[decided];
+            let mut next = vec![(decided + 1, chosen, weight, value)];
+            if weight + w <= self.capacity {
+                next.push((decided + 1, chosen | 1 << decided, weight + w, value + v));
+            }
+            next
+        }
+    }
+
+    // Try every subset.
+    fn brute_force(knapsack: &Knapsack) -> usize {
+        let n = knapsack.items.len();
+        let mut best = 0;
+        for mask in 0..(1u32 << n) {
+            let (weight, value) = (0..n)
+                .filter(|i| mask & (1 << i) != 0)
+                .fold((0, 0), |(w, v), i| (w + knapsack.items[i].0, v + knapsack.items[i].1));
+            if weight <= knapsack.capacity && value > best {
+                best = value;
+            }
+        }
+        best
+    }
+
+    #[test]
+    fn test_knapsack() {
+        let knapsack = Knapsack { items: vec![(12, 4), (2, 2), (1, 1), (1, 2), (4, 10)], capacity: 15 };
+        let mut solver = BranchAndBound::new(&knapsack);
+        let best = solver.solve();
+        assert_eq!(best.3, 15);
+        assert_eq!(best.1, 0b11110);
+        let stats = solver.stats();
+        assert!(stats.expanded > 0);
+        assert!(stats.pruned > 0);
+        assert!(stats.max_queue > 0);
+        assert_eq!(stats.improvements.last().unwrap().score, 15);
+        for pair in stats.improvements.windows(2) {
+            assert!(pair[0].score < pair[1].score);
+        }
+    }
+
+    #[test]
+    fn test_random_knapsacks() {
+        random_cases(12345, 50, |next| {
+            let items = (0..10).map(|_| (next(20) + 1, next(30))).collect();
+            let knapsack = Knapsack { items, capacity: next(60) };
+            let best = BranchAndBound::new(&knapsack).solve();
+            assert_eq!(best.3, brute_force(&knapsack));
+        });
+    }
+}
@@ -0,0 +1,360 @@
+// Command line handling for the runner.
+//
+//   rs_aoc2022 [--day SPEC] [--part 1|2] [--input PATH] [--data DIR] [--strict] [--list]
+//              [--format json|tsv|text] [--time] [--repeat N] [--report FILE]
+//              [--verify] [--answers FILE] [--set NAME] [--jobs N] [--trace FILE]
+//              [--variant NAME] [--export FILE] [--export-option KEY=VALUE]... [--detail]
+//
+// SPEC is a comma separated list of days and inclusive ranges, e.g. "1-5,9".
+// The report format is chosen by FILE's extension, .json or .csv.
+// The answer set to verify against defaults to the name of the data directory.
+
+use crate::output::Format;
+
+pub const USAGE: &str = "\
+Usage: rs_aoc2022 [OPTIONS]
+
+Options:
+  -d, --day SPEC     Days to run, e.g. 16 or 1-5,9 (default: all)
+  -p, --part N       Run only part 1 or part 2 (default: both)
+  -i, --input PATH   Input file to use, - for stdin (requires a single day)
+      --data DIR     Directory holding dayN_input.txt files (default: data_aoc2022)
+      --strict       Treat unrecognized input lines as errors
+  -f, --format FMT   Answer output: text, json (one object per line) or tsv (default: text)
+  -t, --time         Print a table of parse and part timings
+  -r, --repeat N     Run each stage N times and report min/median/max (implies --time)
+      --report FILE  Also write the timings to FILE, as .json or .csv
+      --verify       Check answers against the expected answers file
+      --answers FILE Expected answers file (default: answers.toml)
+      --set NAME     Input set in the answers file (default: the --data directory's name)
+  -j, --jobs N       Solve up to N days at once, and both parts of a day together (default: 1)
+      --trace FILE   Write the progress of every search to FILE, tab separated
+      --variant NAME Solve the selected days another way (see --list)
+      --export FILE  Write a picture of the day's puzzle to FILE, in the format its
+                     extension names (requires a single day)
+      --export-option KEY=VALUE
+                     Adjust the picture, e.g. window=X0,Y0,X1,Y1 or scale=N (repeatable)
+      --detail       Show how answers were reached, for days that explain them (text format)
+  -l, --list         List the implemented days
+  -h, --help         Show this message";
+
+const MAX_DAY: usize = 25;
+
+#[derive(Debug, PartialEq)]
+pub struct Options {
+    pub days: Option<Vec<usize>>,   // None means every implemented day
+    pub part: Option<usize>,        // None means both parts
+    pub input: Option<String>,
+    pub data_dir: String,
+    pub strict: bool,
+    pub help: bool,
+    pub list: bool,
+    pub format: Format,
+    pub time: bool,
+    pub repeat: usize,
+    pub report: Option<String>,
+    pub verify: bool,
+    pub answers: String,
+    pub set: Option<String>,
+    pub jobs: usize,
+    pub trace: Option<String>,
+    pub variant: Option<String>,
+    pub export: Option<String>,
+    pub export_options: Vec<(String, String)>,
+    pub detail: bool,
+}
+
+impl Options {
+    pub fn parse(args: &[String]) -> Result<Options, String> {
+        let mut options = Options {
+            days: None,
+            part: None,
+            input: None,
+            data_dir: "data_aoc2022".to_string(),
+            strict: false,
+            help: false,
+            list: false,
+            format: Format::Text,
+            time: false,
+            repeat: 1,
+            report: None,
+            verify: false,
+            answers: "answers.toml".to_string(),
+            set: None,
+            jobs: 1,
+            trace: None,
+            variant: None,
+            export: None,
+            export_options: Vec::new(),
+            detail: false,
+        };
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            // Accept both "--day 5" and "--day=5"
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+                _ => (arg.as_str(), None),
+            };
+
+            match flag {
+                "-h" | "--help" => {
+                    options.help = true;
+                }
+                "--strict" => {
+                    options.strict = true;
+                }
+                "-l" | "--list" => {
+                    options.list = true;
+                }
+                "-t" | "--time" => {
+                    options.time = true;
+                }
+                "--verify" => {
+                    options.verify = true;
+                }
+                "--detail" => {
+                    options.detail = true;
+                }
+                "-d" | "--day" | "-p" | "--part" | "-i" | "--input" | "--data"
+                | "-f" | "--format" | "-r" | "--repeat" | "--report" | "--answers" | "--set"
+                | "-j" | "--jobs" | "--trace" | "--variant" | "--export" | "--export-option" => {
+                    let value = match inline_value {
+                        Some(value) => value,
+                        None => iter.next()
+                            .ok_or(format!("{} requires a value", flag))?
+                            .to_string(),
+                    };
+                    match flag {
+                        "-d" | "--day" => options.days = Some(parse_day_spec(&value)?),
+                        "-p" | "--part" => options.part = Some(parse_part(&value)?),
+                        "-i" | "--input" => options.input = Some(value),
+                        "-f" | "--format" => options.format = Format::parse(&value)?,
+                        "-j" | "--jobs" => options.jobs = parse_count("jobs", &value)?,
+                        "-r" | "--repeat" => {
+                            options.repeat = parse_count("repeat count", &value)?;
+                            options.time = true;
+                        }
+                        "--report" => options.report = Some(parse_report(&value)?),
+                        "--answers" => options.answers = value,
+                        "--set" => options.set = Some(value),
+                        "--trace" => options.trace = Some(value),
+                        "--variant" => options.variant = Some(value),
+                        "--export" => options.export = Some(value),
+                        "--export-option" => {
+                            match value.split_once('=') {
+                                Some((key, value)) => options.export_options.push((key.to_string(), value.to_string())),
+                                None => return Err(format!("--export-option needs KEY=VALUE, not '{}'", value)),
+                            }
+                        }
+                        _ => options.data_dir = value,
+                    }
+                }
+                _ => {
+                    return Err(format!("Unrecognized argument '{}'", arg));
+                }
+            }
+        }
+
+        // An explicit input file, or a picture, only makes sense for one day.
+        for (flag, given) in [("--input", options.input.is_some()), ("--export", options.export.is_some())] {
+            if given {
+                match &options.days {
+                    Some(days) if days.len() == 1 => {}
+                    _ => return Err(format!("{} requires exactly one --day", flag)),
+                }
+            }
+        }
+
+        Ok(options)
+    }
+
+    // Input file for day n, honoring --input and --data.
+    pub fn input_path(&self, n: usize) -> String {
+        match &self.input {
+            Some(path) => path.clone(),
+            None => format!("{}/day{}_input.txt", self.data_dir, n),
+        }
+    }
+
+    // Name of the input set in the answers file.
+    pub fn input_set(&self) -> String {
+        match &self.set {
+            Some(set) => set.clone(),
+            None => {
+                let dir = self.data_dir.trim_end_matches('/');
+                dir.rsplit('/').next().unwrap_or(dir).to_string()
+            }
+        }
+    }
+
+    pub fn wants_part(&self, part: usize) -> bool {
+        match self.part {
+            None => true,
+            Some(p) => p == part,
+        }
+    }
+}
+
+fn parse_day(s: &str) -> Result<usize, String> {
+    match s.trim().parse::<usize>() {
+        Ok(n) if (1..=MAX_DAY).contains(&n) => Ok(n),
+        _ => Err(format!("Invalid day '{}', expected a number from 1 to {}", s, MAX_DAY)),
+    }
+}
+
+// Parse a day list like "1-5,9" into sorted, de-duplicated day numbers.
+pub fn parse_day_spec(spec: &str) -> Result<Vec<usize>, String> {
+    let mut days: Vec<usize> = Vec::new();
+
+    for item in spec.split(',') {
+        match item.split_once('-') {
+            Some((first, last)) => {
+                let first = parse_day(first)?;
+                let last = parse_day(last)?;
+                if first > last {
+                    return Err(format!("Invalid day range '{}'", item));
+                }
+                days.extend(first..=last);
+            }
+            None => {
+                days.push(parse_day(item)?);
+            }
+        }
+    }
+
+    days.sort();
+    days.dedup();
+
+    Ok(days)
+}
+
+fn parse_part(s: &str) -> Result<usize, String> {
+    match s.trim() {
+        "1" => Ok(1),
+        "2" => Ok(2),
+        _ => Err(format!("Invalid part '{}', expected 1 or 2", s)),
+    }
+}
+
+fn parse_count(what: &str, s: &str) -> Result<usize, String> {
+    match s.trim().parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!("Invalid {} '{}', expected a positive number", what, s)),
+    }
+}
+
+fn parse_report(s: &str) -> Result<String, String> {
+    if s.ends_with(".json") || s.ends_with(".csv") {
+        Ok(s.to_string())
+    }
+    else {
+        Err(format!("Report file '{}' must end in .json or .csv", s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn test_day_spec() {
+        assert_eq!(parse_day_spec("16"), Ok(vec![16]));
+        assert_eq!(parse_day_spec("1-5,9"), Ok(vec![1, 2, 3, 4, 5, 9]));
+        assert_eq!(parse_day_spec("9,3-4,4"), Ok(vec![3, 4, 9]));
+        assert!(parse_day_spec("0").is_err());
+        assert!(parse_day_spec("26").is_err());
+        assert!(parse_day_spec("5-1").is_err());
+        assert!(parse_day_spec("x").is_err());
+        assert!(parse_day_spec("").is_err());
+    }
+
+    #[test]
+    fn test_defaults() {
+        let options = Options::parse(&args("")).unwrap();
+        assert_eq!(options.days, None);
+        assert_eq!(options.part, None);
+        assert!(options.wants_part(1) && options.wants_part(2));
+        assert_eq!(options.input_path(7), "data_aoc2022/day7_input.txt");
+        assert_eq!(options.format, Format::Text);
+        assert!(!options.time);
+        assert_eq!(options.repeat, 1);
+        assert_eq!(options.report, None);
+        assert!(!options.verify);
+        assert_eq!(options.answers, "answers.toml");
+        assert_eq!(options.input_set(), "data_aoc2022");
+        assert_eq!(options.jobs, 1);
+        assert_eq!(options.trace, None);
+        assert_eq!(options.variant, None);
+        assert!(!options.detail);
+    }
+
+    #[test]
+    fn test_parse() {
+        let options = Options::parse(&args("--day 16 --part 2 --input foo.txt")).unwrap();
+        assert_eq!(options.days, Some(vec![16]));
+        assert_eq!(options.part, Some(2));
+        assert!(!options.wants_part(1));
+        assert_eq!(options.input_path(16), "foo.txt");
+
+        let options = Options::parse(&args("-d 6 -i -")).unwrap();
+        assert_eq!(options.input_path(6), "-");
+
+        let options = Options::parse(&args("-d 15 --variant lines")).unwrap();
+        assert_eq!(options.variant, Some("lines".to_string()));
+        assert_eq!(options.export, None);
+        assert!(options.export_options.is_empty());
+
+        let options = Options::parse(&args("-d 15 --export map.pgm --export-option scale=10 --export-option=window=0,0,20,20")).unwrap();
+        assert_eq!(options.export, Some("map.pgm".to_string()));
+        assert_eq!(options.export_options, vec![
+            ("scale".to_string(), "10".to_string()),
+            ("window".to_string(), "0,0,20,20".to_string())]);
+
+        let options = Options::parse(&args("--list --strict --detail")).unwrap();
+        assert!(options.list);
+        assert!(options.strict);
+        assert!(options.detail);
+
+        let options = Options::parse(&args("-d 1-3 --data=inputs")).unwrap();
+        assert_eq!(options.days, Some(vec![1, 2, 3]));
+        assert_eq!(options.input_path(2), "inputs/day2_input.txt");
+
+        let options = Options::parse(&args("--format json -j 4 --trace=search.tsv")).unwrap();
+        assert_eq!(options.format, Format::Json);
+        assert_eq!(options.jobs, 4);
+        assert_eq!(options.trace, Some("search.tsv".to_string()));
+
+        let options = Options::parse(&args("--repeat 5 --report=times.csv")).unwrap();
+        assert!(options.time);
+        assert_eq!(options.repeat, 5);
+        assert_eq!(options.report, Some("times.csv".to_string()));
+
+        let options = Options::parse(&args("--verify --data ../inputs/bob/")).unwrap();
+        assert!(options.verify);
+        assert_eq!(options.input_set(), "bob");
+
+        let options = Options::parse(&args("--verify --answers mine.toml --set alice")).unwrap();
+        assert_eq!(options.answers, "mine.toml");
+        assert_eq!(options.input_set(), "alice");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Options::parse(&args("--day")).is_err());
+        assert!(Options::parse(&args("--part 3")).is_err());
+        assert!(Options::parse(&args("--bogus")).is_err());
+        assert!(Options::parse(&args("--input foo.txt")).is_err());
+        assert!(Options::parse(&args("--day 1-2 --input foo.txt")).is_err());
+        assert!(Options::parse(&args("--format yaml")).is_err());
+        assert!(Options::parse(&args("--repeat 0")).is_err());
+        assert!(Options::parse(&args("--jobs 0")).is_err());
+        assert!(Options::parse(&args("--report times.txt")).is_err());
+        assert!(Options::parse(&args("--export map.pgm")).is_err());
+        assert!(Options::parse(&args("-d 15 --export map.pgm --export-option scale")).is_err());
+    }
+}
@@ -1,12 +1,144 @@
+use crate::error::AocError;
+use crate::input::Input;
+use std::fmt;
+use std::io::BufRead;
+
  #[derive(PartialEq)]
  #[derive(Debug)]
  pub enum Answer {
     None,
     Number(usize),
+    Signed(i64),
+    Big(i128),
     Message(String),
+    // A picture, e.g. letters drawn on a screen.  cells is row major, true is lit.
+    Grid { width: usize, height: usize, cells: Vec<bool> },
+    // An answer with an explanation of how it was reached, e.g. the plan that
+    // achieves it.  Only the answer is checked; the runner shows the detail
+    // when asked (see --detail).
+    Detailed { answer: Box<Answer>, detail: String },
+}
+
+impl Answer {
+    // Answers that are printed on lines of their own.
+    pub fn is_multiline(&self) -> bool {
+        matches!(self.value(), Answer::Message(_) | Answer::Grid { .. })
+    }
+
+    // The answer itself, without any detail.
+    pub fn value(&self) -> &Answer {
+        match self {
+            Answer::Detailed { answer, .. } => answer.value(),
+            _ => self,
+        }
+    }
+
+    pub fn detail(&self) -> Option<&str> {
+        match self {
+            Answer::Detailed { detail, .. } => Some(detail),
+            _ => None,
+        }
+    }
+
+    pub fn without_detail(self) -> Answer {
+        match self {
+            Answer::Detailed { answer, .. } => answer.without_detail(),
+            _ => self,
+        }
+    }
+}
+
+// Grids are drawn with '#' for lit cells and '.' for dark ones, each row
+// ending in a newline.  Detail is left out.
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Answer::None => write!(f, "No Answer"),
+            Answer::Number(val) => write!(f, "{}", val),
+            Answer::Signed(val) => write!(f, "{}", val),
+            Answer::Big(val) => write!(f, "{}", val),
+            Answer::Message(s) => write!(f, "{}", s),
+            Answer::Grid { width, height, cells } => {
+                for y in 0..*height {
+                    for x in 0..*width {
+                        write!(f, "{}", if cells[y*width + x] { '#' } else { '.' })?;
+                    }
+                    writeln!(f)?;
+                }
+                Ok(())
+            }
+            Answer::Detailed { answer, .. } => write!(f, "{}", answer),
+        }
+    }
 }
 
-pub trait Day {
+// Days are solved on worker threads (see --jobs), and both parts of a day
+// may run at once, so they must be Send and Sync.
+pub trait Day: Send + Sync {
+    // Construct the puzzle from its input.
+    fn parse(input: &Input) -> Result<Self, AocError> where Self: Sized;
+
+    // Convenience constructors.  The runner goes through the registry instead,
+    // so these are mostly for the tests.
+
+    // Construct the puzzle from its input file.
+    #[allow(dead_code)]
+    fn load(filename: &str) -> Result<Self, AocError> where Self: Sized {
+        Self::parse(&Input::open(filename)?)
+    }
+
+    // Construct the puzzle from any reader.
+    #[allow(dead_code)]
+    fn from_reader<R: BufRead>(reader: R) -> Result<Self, AocError> where Self: Sized {
+        Self::parse(&Input::from_reader("<reader>", reader)?)
+    }
+
+    // Construct the puzzle from input held in a string.
+    #[allow(dead_code)]
+    fn from_str(s: &str) -> Result<Self, AocError> where Self: Sized {
+        Self::parse(&Input::from_text(s))
+    }
+
+    // Days that can be solved more than one way name the ways in the
+    // registry, and the runner passes the one asked for (see --variant) here
+    // before solving.  Any other name is an error.
+    fn set_variant(&mut self, variant: &str) -> Result<(), String> {
+        Err(format!("no variant '{}'", variant))
+    }
+
+    // A picture of the puzzle for the runner's --export, in the format named
+    // by the file's extension and adjusted by any --export-option settings.
+    fn export(&self, format: &str, _options: &[(String, String)]) -> Result<Vec<u8>, String> {
+        Err(format!("no picture to export as '{}'", format))
+    }
+
     fn part1(&self) -> Answer;
     fn part2(&self) -> Answer;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Answer::None.to_string(), "No Answer");
+        assert_eq!(Answer::Number(42).to_string(), "42");
+        assert_eq!(Answer::Signed(-42).to_string(), "-42");
+        assert_eq!(Answer::Big(1 << 100).to_string(), "1267650600228229401496703205376");
+        assert_eq!(Answer::Message("HELLO".to_string()).to_string(), "HELLO");
+
+        let grid = Answer::Grid { width: 3, height: 2, cells: vec![true, false, true, false, true, false] };
+        assert_eq!(grid.to_string(), "#.#\n.#.\n");
+        assert!(grid.is_multiline());
+        assert!(!Answer::Number(1).is_multiline());
+
+        let detailed = Answer::Detailed { answer: Box::new(Answer::Number(42)), detail: "6 x 7".to_string() };
+        assert_eq!(detailed.to_string(), "42");
+        assert_eq!(detailed.value(), &Answer::Number(42));
+        assert_eq!(detailed.detail(), Some("6 x 7"));
+        assert!(!detailed.is_multiline());
+        assert_eq!(detailed.without_detail(), Answer::Number(42));
+        assert_eq!(Answer::Number(42).detail(), None);
+    }
+}
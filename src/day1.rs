@@ -1,6 +1,6 @@
 use crate::day::{Day, Answer};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use crate::error::AocError;
+use crate::input::Input;
 
 pub struct Day1 {
     // elves[n] is a vector of calorie values, one per item carried by that elf.
@@ -8,37 +8,6 @@ pub struct Day1 {
 }
 
 impl Day1 {
-    pub fn load(filename: &str) -> Day1 {
-        let mut elves: Vec<Vec<usize>> = Vec::new();
-        let mut items: Vec<usize> = Vec::new();
-
-        let file = File::open(filename).unwrap();
-        let reader = BufReader::new(file);
-
-        for line in reader.lines() {
-            let l = &line.unwrap();
-            let calories = l.parse::<usize>();
-            match calories {
-                Err(_) => {
-                    // blank line : previous items vector is done
-                    elves.push(items);
-                    items = Vec::new();
-                }
-                Ok(value) => {
-                    // we have a new item
-                    items.push(value);
-                }
-            }
-        }
-
-        // push last list of items if non-empty
-        if items.len() > 0 {
-            elves.push(items);
-        }
-
-        Day1 { elves }
-    }
-
     fn max_cals(&self) -> usize {
         let mut max = 0;
         for elf in &self.elves {
@@ -66,19 +35,41 @@ impl Day1 {
             totals.push(total);
         }
         totals.sort();
-        totals.reverse();
-
-        let mut sum3 = 0;
-
-        sum3 += totals[0];
-        sum3 += totals[1];
-        sum3 += totals[2];
 
-        sum3
+        // Fewer than three elves just carry what they carry.
+        totals.iter().rev().take(3).sum()
     }
 }
 
 impl Day for Day1 {
+    fn parse(input: &Input) -> Result<Day1, AocError> {
+        let mut elves: Vec<Vec<usize>> = Vec::new();
+        let mut items: Vec<usize> = Vec::new();
+
+        for line in input.lines() {
+            let calories = line.text.parse::<usize>();
+            match calories {
+                Err(_) => {
+                    // blank line : previous items vector is done
+                    line.unmatched()?;
+                    elves.push(items);
+                    items = Vec::new();
+                }
+                Ok(value) => {
+                    // we have a new item
+                    items.push(value);
+                }
+            }
+        }
+
+        // push last list of items if non-empty
+        if !items.is_empty() {
+            elves.push(items);
+        }
+
+        Ok(Day1 { elves })
+    }
+
     fn part1(&self) -> Answer {
         Answer::Number(self.max_cals())
     }
@@ -91,23 +82,60 @@ impl Day for Day1 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testutil::random_cases;
     //use crate::day::Day;
 
     #[test]
     fn test_load() {
-        let d = Day1::load("examples/day1_example1.txt");
+        let d = Day1::load("examples/day1_example1.txt").unwrap();
         assert_eq!(d.elves.len(), 5);
     }
 
     #[test]
     fn test_max() {
-        let d = Day1::load("examples/day1_example1.txt");
+        let d = Day1::load("examples/day1_example1.txt").unwrap();
         assert_eq!(d.max_cals(), 24000);
     }
 
     #[test]
     fn test_max3() {
-        let d = Day1::load("examples/day1_example1.txt");
+        let d = Day1::load("examples/day1_example1.txt").unwrap();
         assert_eq!(d.max3_cals(), 45000);
     }
+
+    #[test]
+    fn test_few_elves() {
+        let d = Day1::from_str("").unwrap();
+        assert_eq!(d.max_cals(), 0);
+        assert_eq!(d.max3_cals(), 0);
+
+        let d = Day1::from_str("1\n\n2\n").unwrap();
+        assert_eq!(d.max_cals(), 2);
+        assert_eq!(d.max3_cals(), 3);
+    }
+
+    #[test]
+    fn test_random_elves() {
+        // Generate inputs and compare against a direct computation of the totals.
+        random_cases(12345, 50, |next| {
+            let mut text = String::new();
+            let mut totals: Vec<usize> = Vec::new();
+            for _elf in 0..3 + next(20) {
+                let mut total = 0;
+                for _item in 0..1 + next(5) {
+                    let calories = 1 + next(10000);
+                    total += calories;
+                    text += &format!("{}\n", calories);
+                }
+                totals.push(total);
+                text += "\n";
+            }
+            totals.sort();
+            totals.reverse();
+
+            let d = Day1::from_str(&text).unwrap();
+            assert_eq!(d.max_cals(), totals[0]);
+            assert_eq!(d.max3_cals(), totals[0] + totals[1] + totals[2]);
+        });
+    }
 }
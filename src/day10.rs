@@ -1,6 +1,6 @@
 use crate::day::{Day, Answer};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use crate::error::AocError;
+use crate::input::Input;
 use lazy_static::lazy_static;
 use regex::Regex;
 
@@ -9,49 +9,20 @@ enum Op {
     Addx(isize),
 }
 
+const SCREEN_WIDTH: usize = 40;
+
 pub struct Day10 {
     ops: Vec<Op>,
 }
 
 impl Day10 {
-    pub fn load(filename: &str) -> Day10 {
-        let mut ops: Vec<Op> = Vec::new();
-        lazy_static! {
-            static ref ADDX_RE: Regex =
-                Regex::new("addx (-?[0-9]+)").unwrap();
-        }
-
-        let file = File::open(filename).unwrap();
-        let reader = BufReader::new(file);
-
-        for line in reader.lines() {
-            let l = &line.unwrap();
-            if l.contains("noop") {
-                // noop
-                ops.push(Op::Noop);
-            }
-            else {
-                let caps = ADDX_RE.captures(&l);
-                match caps {
-                    Some(caps) => {
-                        // addx
-                        let arg = caps[1].parse::<isize>().unwrap();
-                        ops.push(Op::Addx(arg));
-                    }
-                    None => {}
-                }
-            }
-        }
-
-        Day10 { ops }
-    }
-
-    fn run_ops(&self) -> (usize, String) {
+    // Returns the signal strength sum and the screen's pixels, row by row.
+    fn run_ops(&self) -> (usize, Vec<bool>) {
         let mut cycle = 0;
         let mut countdown = 20;
         let mut signal_sum = 0;
         let mut x = 1;
-        let mut image: String = String::new();
+        let mut pixels: Vec<bool> = Vec::new();
         let mut cursor_x = 0;
 
         for op in &self.ops {
@@ -76,37 +47,57 @@ impl Day10 {
                 }
 
                 // evaluate pixel
-                if (cursor_x >= x-1) && (cursor_x <= x+1) {
-                    // pixel is on
-                    image.push('#');
-                }
-                else {
-                    image.push('.');
-                }
+                pixels.push((cursor_x >= x-1) && (cursor_x <= x+1));
 
                 // update cursor
                 cursor_x += 1;
-                if cursor_x >= 40 {
+                if cursor_x >= SCREEN_WIDTH as isize {
                     cursor_x = 0;
-                    image.push('\n');
                 }
             }
             x = update;
         }
 
-        (signal_sum as usize, image)
+        (signal_sum as usize, pixels)
     }
 }
 
 impl Day for Day10 {
+    fn parse(input: &Input) -> Result<Day10, AocError> {
+        let mut ops: Vec<Op> = Vec::new();
+        lazy_static! {
+            static ref ADDX_RE: Regex =
+                Regex::new("addx (-?[0-9]+)").unwrap();
+        }
+
+        for line in input.lines() {
+            if line.text.contains("noop") {
+                // noop
+                ops.push(Op::Noop);
+            }
+            else {
+                match ADDX_RE.captures(line.text) {
+                    Some(caps) => {
+                        // addx
+                        let arg = line.field(&caps, 1)?;
+                        ops.push(Op::Addx(arg));
+                    }
+                    None => line.unmatched()?,
+                }
+            }
+        }
+
+        Ok(Day10 { ops })
+    }
+
     fn part1(&self) -> Answer {
         let (ss, _image) = self.run_ops();
         Answer::Number(ss)
     }
 
     fn part2(&self) -> Answer {
-        let (_ss, image) = self.run_ops();
-        Answer::Message(image)
+        let (_ss, pixels) = self.run_ops();
+        Answer::Grid { width: SCREEN_WIDTH, height: pixels.len() / SCREEN_WIDTH, cells: pixels }
     }
 }
 
@@ -117,13 +108,13 @@ mod tests {
 
     #[test]
     fn test_load1() {
-        let d = Day10::load("examples/day10_example1.txt");
+        let d = Day10::load("examples/day10_example1.txt").unwrap();
         assert_eq!(d.ops.len(), 3);
     }
 
     #[test]
     fn test_load2() {
-        let d = Day10::load("examples/day10_example2.txt");
+        let d = Day10::load("examples/day10_example2.txt").unwrap();
         assert_eq!(d.ops.len(), 146);
     }
 
@@ -136,16 +127,18 @@ mod tests {
              #####.....#####.....#####.....#####.....\n\
              ######......######......######......####\n\
              #######.......#######.......#######.....\n";
-        let d = Day10::load("examples/day10_example2.txt");
-        let (ss, image) = d.run_ops();
+        let d = Day10::load("examples/day10_example2.txt").unwrap();
+        let (ss, pixels) = d.run_ops();
         assert_eq!(ss, 13140);
+        assert_eq!(pixels.len(), 240);
+        let image = d.part2().to_string();
         print!("{}", image);
         assert_eq!(image, expected_image);
     }
 
     #[test]
     fn test_part1() {
-        let d = Day10::load("examples/day10_example2.txt");
+        let d = Day10::load("examples/day10_example2.txt").unwrap();
         assert_eq!(d.part1(), Answer::Number(13140));
     }
 
@@ -158,7 +151,8 @@ mod tests {
              #####.....#####.....#####.....#####.....\n\
              ######......######......######......####\n\
              #######.......#######.......#######.....\n";
-        let d = Day10::load("examples/day10_example2.txt");
-        assert_eq!(d.part2(), Answer::Message(expected_image.to_string()));
+        let d = Day10::load("examples/day10_example2.txt").unwrap();
+        let cells = expected_image.chars().filter(|c| *c != '\n').map(|c| c == '#').collect();
+        assert_eq!(d.part2(), Answer::Grid { width: 40, height: 6, cells });
     }
 }
@@ -1,7 +1,7 @@
 use crate::day::{Day, Answer};
+use crate::error::AocError;
+use crate::input::Input;
 use std::collections::VecDeque;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
 use lazy_static::lazy_static;
 use regex::Regex;
 
@@ -198,8 +198,8 @@ pub struct Day11 {
     monkeys: Vec<Monkey>,
 }
 
-impl Day11 {
-    pub fn load(filename: &str) -> Day11 {
+impl Day for Day11 {
+    fn parse(input: &Input) -> Result<Day11, AocError> {
         let mut monkeys: Vec<Monkey> = Vec::new();
         let mut monkey: Option<Monkey> = None;
         lazy_static! {
@@ -225,135 +225,112 @@ impl Day11 {
                 Regex::new("new = old \\+ old").unwrap();
         }
 
-        let file = File::open(filename).unwrap();
-        let reader = BufReader::new(file);
-
-        for line in reader.lines() {
-            let l = &line.unwrap();
+        for line in input.lines() {
+            let l = line.text;
 
             // Match "Monkey N:"
-            let caps = MONKEY_RE.captures(&l);
-            match caps {
-                Some(caps) => {
-                    // New monkey starting
-                    // store the last monkey we were working with
-                    match monkey {
-                        Some(m) => {
-                            // println!("Pushing monkey.");
-                            monkeys.push(m);
-                        }
-                        None => {}
-                    }
-
-                    // Create new monkey with id
-                    let id = caps[1].parse::<usize>().unwrap();
-                    monkey = Some(Monkey::new(id));
+            if let Some(caps) = MONKEY_RE.captures(l) {
+                // New monkey starting
+                // store the last monkey we were working with
+                if let Some(m) = monkey {
+                    monkeys.push(m);
                 }
-                None => {}
-            }
-
-            match &mut monkey {
-                None => {},
-                Some(monkey) => {
-                    // Match "Starting items"
-                    let caps = STARTING_RE.captures(&l);
-                    match caps {
-                        Some(caps) => {
-                            // caps[1] is a list of worry values, "65, 79, 98, ..."
-                            let mut start_list: Vec<usize> = Vec::new();
-                            for item in caps[1].split(", ") {
-                                // println!("Parsing as int: '{}'", item);
-                                start_list.push(item.parse::<usize>().unwrap());
-                            }
-                            monkey.set_items(&start_list);
-                        }
-                        None => {}
-                    }
-
-                    // Match "Operation"
-                    let caps2 = OPERATION_RE.captures(&l);
-                    match caps2 {
-                        Some(caps2) => {
-                            let op_str: &str = &caps2[1];
-
-                            // Set operation
-                            match MULTIPLY_RE.captures(&op_str) {
-                                Some(op_caps) => {
-                                    let constant = op_caps[1].parse::<usize>().unwrap();
-                                    monkey.set_op(Operation::Mul(constant));
-                                }
-                                None => {}
-                            }
-                            match ADD_RE.captures(&op_str) {
-                                Some(op_caps) => {
-                                    let constant = op_caps[1].parse::<usize>().unwrap();
-                                    monkey.set_op(Operation::Add(constant));
-                                }
-                                None => {}
-                            }
-                            match SQUARE_RE.captures(&op_str) {
-                                Some(_op_caps) => {
-                                    monkey.set_op(Operation::Square);
-                                }
-                                None => {}
-                            }
-                            match DOUBLE_RE.captures(&op_str) {
-                                Some(_op_caps) => {
-                                    monkey.set_op(Operation::Double);
-                                }
-                                None => {}
-                            }
-                        }
-                        None => {}
-                    }
 
-                    // Match "Test"
-                    let caps = TEST_RE.captures(&l);
-                    match caps {
-                        Some(caps) => {
-                            let divisor = caps[1].parse::<usize>().unwrap();
-                            monkey.set_test(divisor);
-                        }
-                        None => {}
-                    }
+                // Create new monkey with id
+                let id = line.field(&caps, 1)?;
+                monkey = Some(Monkey::new(id));
+                continue;
+            }
 
-                    // Match "If true"
-                    let caps = THROW_TRUE_RE.captures(&l);
-                    match caps {
-                        Some(caps) => {
-                            let other = caps[1].parse::<usize>().unwrap();
-                            monkey.set_throw_true(other);
-                        }
-                        None => {}
-                    }
+            let monkey = match &mut monkey {
+                Some(monkey) => monkey,
+                None => {
+                    line.unmatched()?;
+                    continue;
+                }
+            };
 
-                    // Match "If false"
-                    let caps = THROW_FALSE_RE.captures(&l);
-                    match caps {
-                        Some(caps) => {
-                            let other = caps[1].parse::<usize>().unwrap();
-                            monkey.set_throw_false(other);
-                        }
-                        None => {}
+            if let Some(caps) = STARTING_RE.captures(l) {
+                // caps[1] is a list of worry values, "65, 79, 98, ..."
+                let mut start_list: Vec<usize> = Vec::new();
+                let mut column = caps.get(1).unwrap().start() + 1;
+                for item in caps[1].split(", ") {
+                    match item.parse::<usize>() {
+                        Ok(worry) => start_list.push(worry),
+                        Err(_) => return Err(line.error(column, &format!("invalid worry level '{}'", item))),
                     }
+                    column += item.len() + 2;
+                }
+                monkey.set_items(&start_list);
+            }
+            else if let Some(caps) = OPERATION_RE.captures(l) {
+                let op_str: &str = &caps[1];
+
+                // Set operation
+                if let Some(op_caps) = MULTIPLY_RE.captures(op_str) {
+                    let constant = op_caps[1].parse::<usize>()
+                        .map_err(|e| line.error(caps.get(1).unwrap().start() + 1, &e.to_string()))?;
+                    monkey.set_op(Operation::Mul(constant));
                 }
+                else if let Some(op_caps) = ADD_RE.captures(op_str) {
+                    let constant = op_caps[1].parse::<usize>()
+                        .map_err(|e| line.error(caps.get(1).unwrap().start() + 1, &e.to_string()))?;
+                    monkey.set_op(Operation::Add(constant));
+                }
+                else if SQUARE_RE.is_match(op_str) {
+                    monkey.set_op(Operation::Square);
+                }
+                else if DOUBLE_RE.is_match(op_str) {
+                    monkey.set_op(Operation::Double);
+                }
+                else {
+                    let column = caps.get(1).unwrap().start() + 1;
+                    return Err(line.error(column, &format!("unsupported operation '{}'", op_str)));
+                }
+            }
+            else if let Some(caps) = TEST_RE.captures(l) {
+                let divisor = line.field(&caps, 1)?;
+                if divisor == 0 {
+                    return Err(line.error(caps.get(1).unwrap().start() + 1, "divisor must not be zero"));
+                }
+                monkey.set_test(divisor);
+            }
+            else if let Some(caps) = THROW_TRUE_RE.captures(l) {
+                let other = line.field(&caps, 1)?;
+                monkey.set_throw_true(other);
+            }
+            else if let Some(caps) = THROW_FALSE_RE.captures(l) {
+                let other = line.field(&caps, 1)?;
+                monkey.set_throw_false(other);
+            }
+            else {
+                line.unmatched()?;
             }
         }
 
         // Store the last monkey under construction
-        match monkey {
-            Some(m) => {
-                // println!("Pushing last monkey.");
-                monkeys.push(m);
+        if let Some(m) = monkey {
+            monkeys.push(m);
+        }
+
+        // Monkeys are referred to by their position in the list.
+        for (n, m) in monkeys.iter().enumerate() {
+            if m.id != n {
+                return Err(input.invalid(&format!("expected monkey {} but found monkey {}", n, m.id)));
+            }
+            for target in [m.throw_true, m.throw_false] {
+                if target >= monkeys.len() || target == n {
+                    return Err(input.invalid(&format!("monkey {} can't throw to monkey {}", n, target)));
+                }
             }
-            None => {}
+        }
+        if monkeys.len() < 2 {
+            return Err(input.invalid("at least two monkeys are needed"));
         }
 
-        Day11 { monkeys }
+        Ok(Day11 { monkeys })
     }
-}
 
-impl Day for Day11 {
     fn part1(&self) -> Answer {
         let mut sim = Sim::new(&self.monkeys, true);
         for _ in 0..20 {
@@ -384,13 +361,13 @@ mod tests {
 
     #[test]
     fn test_load() {
-        let d = Day11::load("examples/day11_example1.txt");
+        let d = Day11::load("examples/day11_example1.txt").unwrap();
         assert_eq!(d.monkeys.len(), 4);
     }
 
     #[test]
     fn test_round() {
-        let d = Day11::load("examples/day11_example1.txt");
+        let d = Day11::load("examples/day11_example1.txt").unwrap();
         let mut sim: Sim = Sim::new(&d.monkeys, true);
         sim.do_round();
         assert_eq!(sim.monkeys[0].items.len(), 4);
@@ -401,7 +378,7 @@ mod tests {
 
     #[test]
     fn test_round20() {
-        let d = Day11::load("examples/day11_example1.txt");
+        let d = Day11::load("examples/day11_example1.txt").unwrap();
         let mut sim = Sim::new(&d.monkeys, true);
         for n in 0..20 {
             println!("--- Round {} -----------------------------------", n+1);
@@ -416,7 +393,7 @@ mod tests {
 
     #[test]
     fn test_round_pt2() {
-        let d = Day11::load("examples/day11_example1.txt");
+        let d = Day11::load("examples/day11_example1.txt").unwrap();
         let mut sim: Sim = Sim::new(&d.monkeys, false);
         sim.do_round();
         assert_eq!(sim.monkeys[0].items.len(), 4);
@@ -430,7 +407,7 @@ mod tests {
 
     #[test]
     fn test_rounds_pt2() {
-        let d = Day11::load("examples/day11_example1.txt");
+        let d = Day11::load("examples/day11_example1.txt").unwrap();
         let mut sim: Sim = Sim::new(&d.monkeys, false);
 
         for _ in 0..10000 {
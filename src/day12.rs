@@ -1,150 +1,109 @@
+use crate::astar::{AStarSearch, Found, Problem};
 use crate::day::{Day, Answer};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use crate::error::AocError;
+use crate::grid::Grid;
+use crate::input::Input;
+use crate::observer::Stats;
 
 pub struct Day12 {
     start: (usize, usize),
     end: (usize, usize),
-    map: Vec<Vec<usize>>,
+    map: Grid<usize>,
 }
 
-impl Day12 {
-    pub fn load(filename: &str) -> Day12 {
-        let mut map: Vec<Vec<usize>> = Vec::new();
-
-        let file = File::open(filename).unwrap();
-        let reader = BufReader::new(file);
-
-        let mut start = (0, 0);
-        let mut end = (0, 0);
-        let mut col_no = 0;
-        let mut row_no = 0;
-
-        for line in reader.lines() {
-            let l = &line.unwrap();
-            let mut row: Vec<usize> = Vec::new();
-            for c in l.trim().chars() {
-                let height = match c {
-                    'S' => {
-                        start = (row_no, col_no);
-                        1
-                    } 
-                    'E' => {
-                        end = (row_no, col_no);
-                        26
-                    }
-                    'a'..='z' => {
-                        (c as usize) - ('a' as usize) + 1
-                    }
-                    _ => 0
-                };
-                row.push(height);
-                col_no += 1;
-            }
-            map.push(row);
-            row_no += 1;
-            col_no = 0;
-        }
+// Climbing from any of several starts to the end, one square at a time and
+// at most one step up each time.
+struct Climb<'a> {
+    day: &'a Day12,
+    starts: Vec<(usize, usize)>,
+}
 
-        Day12 { start, end, map }
-    }
+impl<'a> Problem for Climb<'a> {
+    type State = (usize, usize);    // (row, col)
 
-    fn path_len(&self)  -> usize {
-        // make a distance matrix initialized with zeros
-        let mut distance: Vec<Vec<usize>> = Vec::new();
-        for row in &self.map {
-            let mut new_row: Vec<usize> = Vec::new();
-            for _ in row {
-                new_row.push(1000000);
-            }
-            distance.push(new_row);
-        }
+    fn starts(&self) -> Vec<(usize, usize)> {
+        self.starts.clone()
+    }
 
-        // set distance to start location to zero
-        distance[self.start.0][self.start.1] = 0;
-
-        // now do a bunch of passes on the table updating all unvisited cells bordering visited ones
-        let mut steps = 0;
-        let mut solved = false;
-        while !solved {
-            steps += 1;
-            for row in 0..self.map.len() {
-                for col in 0..self.map[row].len() {
-                    let h = self.map[row][col];
-
-                    if distance[row][col] == 1000000 {
-                        if ((row > 0) && (distance[row-1][col] == steps-1) && (self.map[row-1][col] >= h-1)) ||
-                           ((row < self.map.len()-1) && (distance[row+1][col] == steps-1) && (self.map[row+1][col] >= h-1)) ||
-                           ((col > 0) && (distance[row][col-1] == steps-1) && (self.map[row][col-1] >= h-1)) ||
-                           ((col < self.map[row].len()-1) && (distance[row][col+1] == steps-1) && (self.map[row][col+1] >= h-1)) { 
-                            // println!("Setting {row},{col} to {steps}");
-                            distance[row][col] = steps;
-                        }
-                    }
-                }
-            }
+    fn successors(&self, &position: &(usize, usize)) -> Vec<((usize, usize), isize)> {
+        let map = &self.day.map;
+        map.neighbours4(position)
+            .filter(|&next| map[next] <= map[position] + 1)
+            .map(|next| (next, 1))
+            .collect()
+    }
 
-            if distance[self.end.0][self.end.1] == steps {
-                // println!("Reached end at {}, {}", self.end.0, self.end.1);
-                solved = true;
-            }
-        }
+    // Every step moves one square, so the Manhattan distance is a lower bound.
+    fn heuristic(&self, &(row, col): &(usize, usize)) -> isize {
+        (row.abs_diff(self.day.end.0) + col.abs_diff(self.day.end.1)) as isize
+    }
 
-        steps
+    fn is_goal(&self, position: &(usize, usize)) -> bool {
+        *position == self.day.end
     }
+}
 
-    fn rev_path_len(&self)  -> usize {
-        // make a distance matrix initialized with zeros
-        let mut distance: Vec<Vec<usize>> = Vec::new();
-        for row in &self.map {
-            let mut new_row: Vec<usize> = Vec::new();
-            for _ in row {
-                new_row.push(1000000);
-            }
-            distance.push(new_row);
-        }
+impl Day12 {
+    // The shortest route from any of starts to the end, and what the search
+    // did to find it.
+    fn search(&self, starts: Vec<(usize, usize)>) -> (Option<Found<(usize, usize)>>, Stats) {
+        let climb = Climb { day: self, starts };
+        let mut search = AStarSearch::new(&climb);
+        let found = search.search();
+
+        (found, search.stats().clone())
+    }
 
-        // set distance to end location to zero
-        distance[self.end.0][self.end.1] = 0;
-
-        // now do a bunch of passes on the table updating all unvisited cells bordering visited ones
-        let mut steps = 0;
-        let mut solved = false;
-        while !solved {
-            steps += 1;
-            for row in 0..self.map.len() {
-                for col in 0..self.map[row].len() {
-                    let h = self.map[row][col];
-
-                    if distance[row][col] == 1000000 {
-                        if ((row > 0) && (distance[row-1][col] == steps-1) && (self.map[row-1][col] <= h+1)) ||
-                           ((row < self.map.len()-1) && (distance[row+1][col] == steps-1) && (self.map[row+1][col] <= h+1)) ||
-                           ((col > 0) && (distance[row][col-1] == steps-1) && (self.map[row][col-1] <= h+1)) ||
-                           ((col < self.map[row].len()-1) && (distance[row][col+1] == steps-1) && (self.map[row][col+1] <= h+1)) { 
-                            // println!("Setting {row},{col} to {steps}");
-                            distance[row][col] = steps;
-
-                            // check for reached level 1
-                            if self.map[row][col] == 1 {
-                                solved = true;
-                            }
-                        }
-                    }
-                }
-            }
-        }
+    // Every square of the lowest height, for searching from all of them at
+    // once.
+    fn lowest(&self) -> Vec<(usize, usize)> {
+        self.map.find(|&height| height == 1).collect()
+    }
 
-        steps
+    // The number of steps, and how much of the map the search looked at.
+    fn answer(&self, starts: Vec<(usize, usize)>) -> Answer {
+        let (found, stats) = self.search(starts);
+        let answer = match found {
+            Some(found) => Answer::Number(found.steps()),
+            None => Answer::None,
+        };
+        let detail = format!("A* expanded {} of {} squares and generated {}.",
+                             stats.expanded, self.map.width() * self.map.height(), stats.generated);
+
+        Answer::Detailed { answer: Box::new(answer), detail }
     }
 }
 
 impl Day for Day12 {
+    fn parse(input: &Input) -> Result<Day12, AocError> {
+        let chars = Grid::parse(input, "height map", |c| match c {
+            'S' | 'E' | 'a'..='z' => Some(c),
+            _ => None,
+        })?;
+
+        let starts: Vec<(usize, usize)> = chars.find(|&c| c == 'S').collect();
+        let ends: Vec<(usize, usize)> = chars.find(|&c| c == 'E').collect();
+        let map = chars.map(|&c| match c {
+            'S' => 1,
+            'E' => 26,
+            _ => (c as usize) - ('a' as usize) + 1,
+        });
+
+        match (&starts[..], &ends[..]) {
+            (&[start], &[end]) => Ok(Day12 { start, end, map }),
+            ([], _) => Err(input.invalid("no start position 'S' in height map")),
+            (_, []) => Err(input.invalid("no end position 'E' in height map")),
+            ([_, _, ..], _) => Err(input.invalid("more than one start position 'S' in height map")),
+            (_, _) => Err(input.invalid("more than one end position 'E' in height map")),
+        }
+    }
+
     fn part1(&self) -> Answer {
-        Answer::Number(self.path_len())
+        self.answer(vec![self.start])
     }
 
     fn part2(&self) -> Answer {
-        Answer::Number(self.rev_path_len())
+        self.answer(self.lowest())
     }
 }
 
@@ -155,22 +114,56 @@ mod tests {
 
     #[test]
     fn test_load() {
-        let d = Day12::load("examples/day12_example1.txt");
-        assert_eq!(d.map.len(), 5);
-        assert_eq!(d.map[0].len(), 8);
+        let d = Day12::load("examples/day12_example1.txt").unwrap();
+        assert_eq!(d.map.height(), 5);
+        assert_eq!(d.map.width(), 8);
         assert_eq!(d.start, (0, 0));
         assert_eq!(d.end, (2, 5));
     }
 
     #[test]
     fn test_path_len() {
-        let d = Day12::load("examples/day12_example1.txt");
-        assert_eq!(d.path_len(), 31);
+        let d = Day12::load("examples/day12_example1.txt").unwrap();
+        let answer = d.part1();
+        assert_eq!(answer.value(), &Answer::Number(31));
+        assert!(answer.detail().unwrap().starts_with("A* expanded "));
+    }
+
+    #[test]
+    fn test_best_start_len() {
+        let d = Day12::load("examples/day12_example1.txt").unwrap();
+        assert_eq!(d.part2().value(), &Answer::Number(29));
     }
 
     #[test]
-    fn test_rev_path_len() {
-        let d = Day12::load("examples/day12_example1.txt");
-        assert_eq!(d.rev_path_len(), 29);
+    fn test_route() {
+        let d = Day12::load("examples/day12_example1.txt").unwrap();
+        let route = d.search(vec![d.start]).0.unwrap().path;
+        assert_eq!(route.len(), 32);
+        assert_eq!(route[0], d.start);
+        assert_eq!(route[31], d.end);
+        for step in route.windows(2) {
+            let ((r0, c0), (r1, c1)) = (step[0], step[1]);
+            assert_eq!(r0.abs_diff(r1) + c0.abs_diff(c1), 1);
+            assert!(d.map[step[1]] <= d.map[step[0]] + 1);
+        }
+    }
+
+    #[test]
+    fn test_unreachable() {
+        let d = Day12::from_str("Sbz\nzzE\n").unwrap();
+        assert_eq!(d.part1().value(), &Answer::None);
+        assert_eq!(d.part2().value(), &Answer::None);
+    }
+
+    #[test]
+    fn test_start_and_end() {
+        for (text, message) in [("abc\nabE\n", "no start"), ("Sbc\nabc\n", "no end"),
+                                ("SbS\nabE\n", "more than one start"), ("SbE\nEbc\n", "more than one end")] {
+            match Day12::from_str(text) {
+                Err(AocError::Invalid { message: m, .. }) => assert!(m.starts_with(message), "{}", m),
+                _ => panic!("expected {} for {:?}", message, text),
+            }
+        }
     }
 }
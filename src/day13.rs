@@ -1,6 +1,6 @@
 use crate::day::{Day, Answer};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use crate::error::AocError;
+use crate::input::Input;
 use std::cmp::Ordering;
 use core::cmp::{PartialOrd, PartialEq};
 use sorted_vec::SortedVec;
@@ -71,12 +71,17 @@ impl PartialOrd for PacketComponent {
 }
 
 impl PacketList {
-    // parse a &str into a PacketList
-    pub fn new(s: &str) -> PacketList {
-        let s_chars = s.chars().collect();
-        let (_index, list) = PacketList::parse(&s_chars, 0);
+    // parse a &str into a PacketList.  On failure, returns the (0-based)
+    // index of the character where the packet stopped making sense.
+    pub fn new(s: &str) -> Result<PacketList, usize> {
+        let s_chars: Vec<char> = s.chars().collect();
+        let (index, list) = PacketList::parse(&s_chars, 0)?;
+        if index != s_chars.len() {
+            // trailing characters after the packet
+            return Err(index);
+        }
 
-        list        
+        Ok(list)
     }
 
     pub fn from_int(n: usize) -> PacketList {
@@ -84,55 +89,50 @@ impl PacketList {
         PacketList { list }
     }
 
-    fn parse(s_chars: &Vec<char>, mut index: usize) -> (usize, PacketList) {
+    fn parse(s_chars: &[char], mut index: usize) -> Result<(usize, PacketList), usize> {
         // create an empty list
         let mut list = Vec::new();
 
         // consume the opening '['
-        assert_eq!('[', s_chars[index]);
-        // println!(" consume {}: '{}'", index, s_chars[index]);
+        if s_chars.get(index) != Some(&'[') {
+            return Err(index);
+        }
         index += 1;
 
         loop {
-            if s_chars[index] == '[' {
-                // do the recursion thing
-                // println!("[ : recurse");
-                let (new_index, sub_list) = PacketList::parse(s_chars, index);
-                // assert_eq!(s_chars[new_index], ']');
-                // println!(" consume {}: '{}'", new_index, s_chars[new_index]);
-                index = new_index;
-
-                // push sub_list into list
-                list.push(PacketComponent::List(sub_list));
-            }
-            else if s_chars[index] == ']' {
-                // pop out of recursion
-                // println!("consume {}: '{}'", index, s_chars[index]);
-                // println!("] : exit recursive call.");
-                return (index+1, PacketList{list});
-            }
-            else if s_chars[index].is_ascii_digit() {
-                // collect digits into a number
-                let mut value = 0;
-                while s_chars[index].is_ascii_digit() {
-                    value *= 10;
-                    value += s_chars[index].to_digit(10).unwrap() as usize;
-                    // println!(" consume {}: '{}'", index, s_chars[index]);
-                    index += 1;
+            match s_chars.get(index) {
+                Some('[') => {
+                    // do the recursion thing
+                    let (new_index, sub_list) = PacketList::parse(s_chars, index)?;
+                    index = new_index;
+
+                    // push sub_list into list
+                    list.push(PacketComponent::List(sub_list));
+                }
+                Some(']') => {
+                    // pop out of recursion
+                    return Ok((index+1, PacketList{list}));
                 }
-                // println!(" unconsume {}: '{}'", index, s_chars[index]);
-                // index -= 1;
+                Some(c) if c.is_ascii_digit() => {
+                    // collect digits into a number
+                    let mut value = 0;
+                    while let Some(digit) = s_chars.get(index).and_then(|c| c.to_digit(10)) {
+                        value *= 10;
+                        value += digit as usize;
+                        index += 1;
+                    }
 
-                // store value in list
-                list.push(PacketComponent::Integer(value));
-            }
-            else if s_chars[index] == ',' {
-                // We can ignore these
-                // println!(" consume {}: '{}'", index, s_chars[index]);
-                index += 1;
-            }
-            else {
-                panic!("Unexpected character in packet list.");
+                    // store value in list
+                    list.push(PacketComponent::Integer(value));
+                }
+                Some(',') => {
+                    // We can ignore these
+                    index += 1;
+                }
+                _ => {
+                    // Unexpected character or end of packet.
+                    return Err(index);
+                }
             }
         }
 
@@ -195,44 +195,6 @@ pub struct Day13 {
 }
 
 impl Day13 {
-    pub fn load(filename: &str) -> Day13 {
-        let mut pairs: Vec<(PacketList, PacketList)> = Vec::new();
-
-        let file = File::open(filename).unwrap();
-        let reader = BufReader::new(file);
-
-        let mut packet1: Option<PacketList> = None;
-
-        for line in reader.lines() {
-
-            match line {
-                Ok(line) => {
-                    // println!("parsing {}", line);
-                    let trimmed = line.trim();
-                    if trimmed.len() > 0 {
-                        match packet1 {
-                            Some(p1) => {
-                                let p2 = PacketList::new(trimmed);
-                                let pair = (p1, p2);
-                                pairs.push(pair);
-                                packet1 = None;
-                            }
-                            None => {
-                                packet1 = Some(PacketList::new(trimmed));
-                            }
-                        }
-                        
-                    }
-                }
-                Err(_) => {
-                    // Reached end of file.
-                }
-            }
-        }
-
-        Day13 { pairs }
-    }
-
     pub fn ordered_right_sum(&self) -> usize {
         let mut sum = 0;
         let mut index = 0;
@@ -249,8 +211,8 @@ impl Day13 {
 
     pub fn decode_key(&self) -> usize {
         let mut packets: SortedVec<&PacketList> = SortedVec::new();
-        let divider1 = PacketList::new("[[2]]");
-        let divider2 = PacketList::new("[[6]]");
+        let divider1 = PacketList::new("[[2]]").unwrap();
+        let divider2 = PacketList::new("[[6]]").unwrap();
 
         // throw divider packets into the empty packet list
         packets.insert(&divider1);  
@@ -273,6 +235,49 @@ impl Day13 {
 }
 
 impl Day for Day13 {
+    fn parse(input: &Input) -> Result<Day13, AocError> {
+        let mut pairs: Vec<(PacketList, PacketList)> = Vec::new();
+
+        let mut packet1: Option<PacketList> = None;
+
+        for line in input.lines() {
+            let trimmed = line.text.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+
+            let packet = match PacketList::new(trimmed) {
+                Ok(packet) => packet,
+                Err(index) => {
+                    // index counts chars, but columns count bytes, as for
+                    // every other parse error.
+                    let (offset, message) = match trimmed.char_indices().nth(index) {
+                        Some((offset, c)) => (offset, format!("unexpected character '{}' in packet", c)),
+                        None => (trimmed.len(), "unexpected end of packet".to_string()),
+                    };
+                    let column = line.text.len() - line.text.trim_start().len() + offset + 1;
+                    return Err(line.error(column, &message));
+                }
+            };
+
+            match packet1 {
+                Some(p1) => {
+                    pairs.push((p1, packet));
+                    packet1 = None;
+                }
+                None => {
+                    packet1 = Some(packet);
+                }
+            }
+        }
+
+        if packet1.is_some() {
+            return Err(input.invalid("last packet has no partner"));
+        }
+
+        Ok(Day13 { pairs })
+    }
+
     fn part1(&self) -> Answer {
         Answer::Number(self.ordered_right_sum())
     }
@@ -289,77 +294,77 @@ mod tests {
 
     #[test]
     fn test_load() {
-        let d = Day13::load("examples/day13_example1.txt");
+        let d = Day13::load("examples/day13_example1.txt").unwrap();
         assert_eq!(d.pairs.len(), 8);
     }
 
     #[test]
     fn test_ordering_eg1() {
-        let p1 = PacketList::new("[1,1,3,1,1]");
-        let p2 = PacketList::new("[1,1,5,1,1]");
+        let p1 = PacketList::new("[1,1,3,1,1]").unwrap();
+        let p2 = PacketList::new("[1,1,5,1,1]").unwrap();
 
         assert!(p1 < p2);
     }
 
     #[test]
     fn test_ordering_eg2() {
-        let p1 = PacketList::new("[[1],[2,3,4]]");
-        let p2 = PacketList::new("[[1],4]");
+        let p1 = PacketList::new("[[1],[2,3,4]]").unwrap();
+        let p2 = PacketList::new("[[1],4]").unwrap();
 
         assert!(p1 < p2);
     }
 
     #[test]
     fn test_ordering_eg3() {
-        let p1 = PacketList::new("[9]");
-        let p2 = PacketList::new("[[8,7,6]]");
+        let p1 = PacketList::new("[9]").unwrap();
+        let p2 = PacketList::new("[[8,7,6]]").unwrap();
 
         assert!(p1 >p2);
     }
 
     #[test]
     fn test_ordering_eg4() {
-        let p1 = PacketList::new("[[4,4],4,4]");
-        let p2 = PacketList::new("[[4,4],4,4,4]");
+        let p1 = PacketList::new("[[4,4],4,4]").unwrap();
+        let p2 = PacketList::new("[[4,4],4,4,4]").unwrap();
 
         assert!(p1 < p2);
     }
 
     #[test]
     fn test_ordering_eg5() {
-        let p1 = PacketList::new("[7,7,7,7]");
-        let p2 = PacketList::new("[7,7,7]");
+        let p1 = PacketList::new("[7,7,7,7]").unwrap();
+        let p2 = PacketList::new("[7,7,7]").unwrap();
 
         assert!(p1 > p2);
     }
 
     #[test]
     fn test_ordering_eg6() {
-        let p1 = PacketList::new("[]");
-        let p2 = PacketList::new("[3]");
+        let p1 = PacketList::new("[]").unwrap();
+        let p2 = PacketList::new("[3]").unwrap();
 
         assert!(p1 < p2);
     }
 
     #[test]
     fn test_ordering_eg7() {
-        let p1 = PacketList::new("[[[]]]");
-        let p2 = PacketList::new("[[]]");
+        let p1 = PacketList::new("[[[]]]").unwrap();
+        let p2 = PacketList::new("[[]]").unwrap();
 
         assert!(p1 > p2);
     }
 
     #[test]
     fn test_ordering_eg8() {
-        let p1 = PacketList::new("[1,[2,[3,[4,[5,6,7]]]],8,9]");
-        let p2 = PacketList::new("[1,[2,[3,[4,[5,6,0]]]],8,9]");
+        let p1 = PacketList::new("[1,[2,[3,[4,[5,6,7]]]],8,9]").unwrap();
+        let p2 = PacketList::new("[1,[2,[3,[4,[5,6,0]]]],8,9]").unwrap();
 
         assert!(p1 > p2);
     }
 
     #[test]
     fn test_ordered_right_sum() {
-        let d = Day13::load("examples/day13_example1.txt");
+        let d = Day13::load("examples/day13_example1.txt").unwrap();
         let sum = d.ordered_right_sum();
         assert_eq!(sum, 13);
     }
@@ -367,22 +372,54 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        let d = Day13::load("examples/day13_example1.txt");
+        let d = Day13::load("examples/day13_example1.txt").unwrap();
         let result = d.part1();
         assert_eq!(result, Answer::Number(13));
     }
 
     #[test]
     fn test_decoder_key() {
-        let d = Day13::load("examples/day13_example1.txt");
+        let d = Day13::load("examples/day13_example1.txt").unwrap();
         let result = d.decode_key();
         assert_eq!(result, 140);
     }
 
     #[test]
     fn test_part2() {
-        let d = Day13::load("examples/day13_example1.txt");
+        let d = Day13::load("examples/day13_example1.txt").unwrap();
         let result = d.part2();
         assert_eq!(result, Answer::Number(140));
     }
+
+    #[test]
+    fn test_bad_packet() {
+        assert_eq!(PacketList::new("[1,x]").err(), Some(3));
+        assert_eq!(PacketList::new("[1,[2]").err(), Some(6));
+        assert_eq!(PacketList::new("[1]2").err(), Some(3));
+
+        match Day13::load("examples/day13_bad1.txt") {
+            Err(AocError::Parse { line, column, .. }) => assert_eq!((line, column), (2, 4)),
+            _ => panic!("expected a parse error"),
+        }
+
+        // The ideographic space is three bytes of leading whitespace.
+        match Day13::from_str("[1]\n\u{3000}[1,x]\n") {
+            Err(AocError::Parse { line, column, message, .. }) => {
+                assert_eq!((line, column), (2, 7));
+                assert_eq!(message, "unexpected character 'x' in packet");
+            }
+            _ => panic!("expected a parse error"),
+        }
+        match Day13::from_str("[1]\n[1,\n") {
+            Err(AocError::Parse { line, column, .. }) => assert_eq!((line, column), (2, 4)),
+            _ => panic!("expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn test_from_str() {
+        let d = Day13::from_str("[1,1,3,1,1]\n[1,1,5,1,1]\n\n[9]\n[[8,7,6]]\n").unwrap();
+        assert_eq!(d.pairs.len(), 2);
+        assert_eq!(d.part1(), Answer::Number(1));
+    }
 }
@@ -1,107 +1,142 @@
-use std::collections::HashSet;
 use crate::day::{Day, Answer};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use crate::error::AocError;
+use crate::grid::Grid;
+use crate::input::Input;
 use lazy_static::lazy_static;
 use regex::Regex;
 
-struct CavernState {
-    stone: HashSet<(isize, isize)>,  // locations of stones
-    sand: HashSet<(isize, isize)>,   // locations of sand
-    lowest_y: isize,
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Material {
+    Air,
+    Stone,
+    Sand,
 }
 
-impl CavernState {
-    pub fn init(lines: &Vec<Vec<(isize, isize)>>) -> CavernState {
-        let mut stone = HashSet::new();
-        let sand = HashSet::new();
-        let mut lowest_y = 0;
+// What's below the lowest stone.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Bottom {
+    Abyss,  // sand that falls past the lowest stone is lost
+    Floor,  // an endless floor two below the lowest stone
+}
+
+// Where sand pours in, unless told otherwise.
+const SOURCE: (isize, isize) = (500, 0);
+
+// The cavern on a dense grid, just big enough for the stone and any sand that
+// can settle, with the path the current grain has fallen.  Each grain falls
+// the same way as the one before until that one's resting place, so the next
+// grain carries on from the last free position on the path rather than
+// starting again from the source.
+struct Cavern {
+    grid: Grid<Material>,           // (row, col) is (y - origin.1, x - origin.0)
+    origin: (isize, isize),         // (x, y) of the grid's top left
+    bottom: Bottom,
+    path: Vec<(usize, usize)>,      // from the source to the falling grain
+}
 
+impl Cavern {
+    pub fn new(lines: &[Vec<(isize, isize)>], source: (isize, isize), bottom: Bottom) -> Cavern {
+        let mut stones: Vec<(isize, isize)> = Vec::new();
         for line in lines {
             for n in 0..line.len()-1 {
                 let (x1, y1) = line[n];
                 let (x2, y2) = line[n+1];
-                let delta_x: isize = if x2>x1 {1} else if x2<x1 {-1} else {0};
-                let delta_y: isize = if y2>y1 {1} else if y2<y1 {-1} else {0};
+                let delta_x = (x2 - x1).signum();
+                let delta_y = (y2 - y1).signum();
 
+                // Stones in cells denoted by this line segment.
                 let mut x = x1;
                 let mut y = y1;
-
-                if y > lowest_y {
-                    lowest_y = y;
-                }
-                // Insert stones in cells denoted by this line segment.
                 while (x,y) != (x2, y2) {
-                    stone.insert((x, y));
+                    stones.push((x, y));
                     x += delta_x;
                     y += delta_y;
                 }
-                stone.insert((x, y));
-
-                if y > lowest_y {
-                    lowest_y = y;
-                }
+                stones.push((x, y));
             }
         }
 
-        CavernState {stone, sand, lowest_y}
-    }
-
-    // inserts a grain of sand, returns false if no more sand can be inserted
-    fn insert_sand(&mut self, until_blocked: bool) -> bool {
-        // initial position of new sand grain
-        let mut x: isize = 500;
-        let mut y: isize = 0;
-        let mut can_fall = true;
-
-        while can_fall {
-            let straight = (x, y + 1);
-            let left = (x - 1, y + 1);
-            let right = (x + 1, y + 1);
-
-            if y >= self.lowest_y+1 {
-                // stop when level lowest+1 is reached.
-                can_fall = false;
-            } else if !self.stone.contains(&straight) && !self.sand.contains(&straight) {
-                // fall straight down
-                y += 1;
-            } else if !self.stone.contains(&left) && !self.sand.contains(&left) {
-                // fall down and left
-                y += 1;
-                x -= 1;
-            } else if !self.stone.contains(&right) && !self.sand.contains(&right) {
-                // fall down and left
-                y += 1;
-                x += 1;
-            } else {
-                can_fall = false;
+        // Sand piles up no wider than it falls, so the floor only needs to
+        // reach that far either side of the source.  Without one, sand that
+        // gets past the stone at the sides is lost.
+        let lowest_y = stones.iter().map(|stone| stone.1).max().unwrap_or(source.1);
+        let (bottom_y, spread) = match bottom {
+            Bottom::Abyss => (lowest_y, 0),
+            Bottom::Floor => (lowest_y + 1, (lowest_y + 1 - source.1).max(0)),
+        };
+        let top = stones.iter().map(|stone| stone.1).fold(source.1, isize::min);
+        let left = stones.iter().map(|stone| stone.0).fold(source.0 - spread, isize::min) - 1;
+        let right = stones.iter().map(|stone| stone.0).fold(source.0 + spread, isize::max) + 1;
+
+        let width = (right - left + 1) as usize;
+        let height = (bottom_y - top + 1).max(0) as usize;
+        let mut grid = Grid::new(width, height, Material::Air);
+        for (x, y) in stones {
+            grid[((y - top) as usize, (x - left) as usize)] = Material::Stone;
+        }
+
+        let mut cavern = Cavern { grid, origin: (left, top), bottom, path: Vec::new() };
+        if let Some(source) = cavern.position(source) {
+            if cavern.grid[source] == Material::Air {
+                cavern.path.push(source);
             }
         }
 
-        // Add sand where it came to rest
-        self.sand.insert((x, y));
+        cavern
+    }
 
-        // Return false if no more can be inserted
-        if until_blocked {
-            y != 0
-        }
-        else {
-            y <= self.lowest_y
-        }
+    fn position(&self, (x, y): (isize, isize)) -> Option<(usize, usize)> {
+        let row = usize::try_from(y - self.origin.1).ok()?;
+        let col = usize::try_from(x - self.origin.0).ok()?;
+        if self.grid.contains((row, col)) { Some((row, col)) } else { None }
     }
 
-    fn insert_sand_loop(&mut self, until_blocked: bool) -> usize {
-        let mut inserts = 0;
+    // Drop a grain of sand, returning the (x, y) where it settles, or None
+    // when no more can settle: the source is blocked, or the grain fell into
+    // the abyss, as will every one after it.
+    fn settle(&mut self) -> Option<(isize, isize)> {
+        loop {
+            let &(row, col) = self.path.last()?;
 
-        while self.insert_sand(until_blocked) {
-            inserts += 1;
-        }
-        if until_blocked {
-            // when inserting until blocked, that last one counts.
-            inserts += 1;
+            // Down, then down and left, then down and right.
+            if row + 1 == self.grid.height() {
+                match self.bottom {
+                    Bottom::Abyss => return None,
+                    Bottom::Floor => {}
+                }
+            }
+            else {
+                let below = [(row + 1, col), (row + 1, col.wrapping_sub(1)), (row + 1, col + 1)];
+                let next = below.into_iter().find(|&p| self.grid.get(p).is_none_or(|m| *m == Material::Air));
+                if let Some(next) = next {
+                    if !self.grid.contains(next) {
+                        // Off the side, past every stone.
+                        return None;
+                    }
+                    self.path.push(next);
+                    continue;
+                }
+            }
+
+            self.grid[(row, col)] = Material::Sand;
+            self.path.pop();
+            return Some((self.origin.0 + col as isize, self.origin.1 + row as isize));
         }
+    }
+
+    // Each grain's resting place, in turn, until no more settle.
+    fn grains(&mut self) -> impl Iterator<Item = (isize, isize)> + '_ {
+        std::iter::from_fn(move || self.settle())
+    }
 
-        inserts
+    // The cavern as the puzzle draws it, for debugging.  Don't complain when not in use.
+    #[allow(dead_code)]
+    fn render(&self) -> String {
+        self.grid.render(|material| match material {
+            Material::Air => '.',
+            Material::Stone => '#',
+            Material::Sand => 'o',
+        })
     }
 }
 
@@ -111,89 +146,161 @@ pub struct Day14 {
 }
 
 impl Day14 {
-    pub fn load(filename: &str) -> Day14 {
+    fn get_cavern(&self, bottom: Bottom) -> Cavern {
+        Cavern::new(&self.lines, SOURCE, bottom)
+    }
+}
+
+impl Day for Day14 {
+    fn parse(input: &Input) -> Result<Day14, AocError> {
         let mut lines: Vec<Vec<(isize, isize)>> = Vec::new();
         lazy_static! {
             static ref LINE_RE: Regex =
                 Regex::new("([0-9]+),([0-9]+)").unwrap();
         }
 
-        let file = File::open(filename).unwrap();
-        let reader = BufReader::new(file);
-
-        for line in reader.lines() {
+        for line in input.lines() {
             let mut coords: Vec<(isize, isize)> = Vec::new();
-            let l = &line.unwrap();
 
-            for cap in LINE_RE.captures_iter(l) {
-                let x = cap[1].parse::<isize>().unwrap();
-                let y = cap[2].parse::<isize>().unwrap();
+            for cap in LINE_RE.captures_iter(line.text) {
+                let x = line.field(&cap, 1)?;
+                let y = line.field(&cap, 2)?;
                 coords.push( (x, y) );
             }
-            lines.push(coords);
+
+            if coords.is_empty() {
+                line.unmatched()?;
+            }
+            else {
+                lines.push(coords);
+            }
         }
 
-        Day14 { lines }
+        Ok(Day14 { lines })
     }
 
-    fn get_cavern(&self) -> CavernState {
-        CavernState::init(&self.lines)
-    }
-}
-
-impl Day for Day14 {
     fn part1(&self) -> Answer {
-        let mut cavern = self.get_cavern();
-        Answer::Number(cavern.insert_sand_loop(false))
+        let mut cavern = self.get_cavern(Bottom::Abyss);
+        Answer::Number(cavern.grains().count())
     }
 
     fn part2(&self) -> Answer {
-        let mut cavern = self.get_cavern();
-        Answer::Number(cavern.insert_sand_loop(true))
+        // The last grain settles on the source, blocking it.
+        let mut cavern = self.get_cavern(Bottom::Floor);
+        Answer::Number(cavern.grains().count())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    //use crate::day::Day;
+    use std::collections::HashSet;
 
     #[test]
     fn test_load() {
-        let d = Day14::load("examples/day14_example1.txt");
+        let d = Day14::load("examples/day14_example1.txt").unwrap();
         assert_eq!(d.lines.len(), 2);
     }
 
+    // Drop each grain from the source, as the puzzle describes.
+    fn naive_grains(lines: &[Vec<(isize, isize)>], source: (isize, isize), bottom: Bottom) -> Vec<(isize, isize)> {
+        let cavern = Cavern::new(lines, source, bottom);
+        let mut blocked: HashSet<(isize, isize)> = cavern.grid.find(|m| *m == Material::Stone)
+            .map(|(row, col)| (cavern.origin.0 + col as isize, cavern.origin.1 + row as isize))
+            .collect();
+        let lowest_y = blocked.iter().map(|stone| stone.1).max().unwrap();
+
+        let mut grains = Vec::new();
+        while !blocked.contains(&source) {
+            let (mut x, mut y) = source;
+            loop {
+                if y == lowest_y + 1 {
+                    break;
+                }
+                match [x, x - 1, x + 1].into_iter().find(|nx| !blocked.contains(&(*nx, y + 1))) {
+                    Some(nx) => (x, y) = (nx, y + 1),
+                    None => break,
+                }
+            }
+            if y > lowest_y && bottom == Bottom::Abyss {
+                break;
+            }
+            blocked.insert((x, y));
+            grains.push((x, y));
+        }
+
+        grains
+    }
+
     #[test]
     fn test_cavern() {
-        let d = Day14::load("examples/day14_example1.txt");
-        let cavern = d.get_cavern();
-        assert_eq!(cavern.stone.len(), 20);
+        let d = Day14::load("examples/day14_example1.txt").unwrap();
+        let cavern = d.get_cavern(Bottom::Abyss);
+        assert_eq!(cavern.grid.find(|m| *m == Material::Stone).count(), 20);
+        assert_eq!(cavern.origin, (493, 0));
+        assert_eq!(cavern.grid.height(), 10);
+
+        // Wide enough for the pile on the floor.
+        let cavern = d.get_cavern(Bottom::Floor);
+        assert_eq!(cavern.origin, (489, 0));
+        assert_eq!(cavern.grid.width(), 23);
+        assert_eq!(cavern.grid.height(), 11);
     }
 
     #[test]
-    fn test_insert_sand_part1() {
-        let d = Day14::load("examples/day14_example1.txt");
-        let mut cavern = d.get_cavern();
-        assert_eq!(cavern.insert_sand_loop(false), 24);
+    fn test_render() {
+        let d = Day14::load("examples/day14_example1.txt").unwrap();
+        let mut cavern = d.get_cavern(Bottom::Abyss);
+        assert_eq!(cavern.grains().count(), 24);
+        assert_eq!(cavern.render(),
+            "............\n\
+             ............\n\
+             .......o....\n\
+             ......ooo...\n\
+             .....#ooo##.\n\
+             ....o#ooo#..\n\
+             ...###ooo#..\n\
+             .....oooo#..\n\
+             ..o.ooooo#..\n\
+             .#########..\n");
     }
 
     #[test]
-    fn test_insert_sand_part2() {
-        let d = Day14::load("examples/day14_example1.txt");
-        let mut cavern = d.get_cavern();
-        assert_eq!(cavern.insert_sand_loop(true), 93);
+    fn test_grains() {
+        let d = Day14::load("examples/day14_example1.txt").unwrap();
+        let mut cavern = d.get_cavern(Bottom::Abyss);
+        let grains: Vec<_> = cavern.grains().take(5).collect();
+        assert_eq!(grains, vec![(500, 8), (499, 8), (501, 8), (500, 7), (498, 8)]);
+
+        // Once a grain is lost to the abyss, nothing more settles.
+        assert_eq!(cavern.grains().count(), 19);
+        assert_eq!(cavern.settle(), None);
+
+        let mut cavern = d.get_cavern(Bottom::Floor);
+        assert_eq!(cavern.grains().last(), Some(SOURCE));
+        assert_eq!(cavern.settle(), None);
+    }
+
+    #[test]
+    fn test_against_naive() {
+        let d = Day14::load("examples/day14_example1.txt").unwrap();
+        for source in [SOURCE, (497, 1), (503, 0), (490, 3)] {
+            for bottom in [Bottom::Abyss, Bottom::Floor] {
+                let grains: Vec<_> = Cavern::new(&d.lines, source, bottom).grains().collect();
+                assert_eq!(grains, naive_grains(&d.lines, source, bottom), "{:?} {:?}", source, bottom);
+            }
+        }
     }
 
     #[test]
     fn test_part1() {
-        let d = Day14::load("examples/day14_example1.txt");
+        let d = Day14::load("examples/day14_example1.txt").unwrap();
         assert_eq!(d.part1(), Answer::Number(24));
     }
 
     #[test]
     fn test_part2() {
-        let d = Day14::load("examples/day14_example1.txt");
+        let d = Day14::load("examples/day14_example1.txt").unwrap();
         assert_eq!(d.part2(), Answer::Number(93));
     }
 }
@@ -1,7 +1,8 @@
-use std::collections::HashSet;
 use crate::day::{Day, Answer};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use crate::error::AocError;
+use crate::grid::Grid;
+use crate::input::Input;
+use crate::intervals::IntervalSet;
 use lazy_static::lazy_static;
 use regex::Regex;
 
@@ -12,75 +13,86 @@ struct SensorBeacon {
     by: isize,
 }
 
-pub struct Day15 {
-    sensor_beacons: Vec<SensorBeacon>,
+impl SensorBeacon {
+    // Manhattan distance to the beacon.  Nothing closer can be a beacon.
+    fn radius(&self) -> isize {
+        (self.sx-self.bx).abs() + (self.sy-self.by).abs()
+    }
+
+    // The sensor in rotated coordinates, u = x + y and v = x - y, where its
+    // coverage is a square: max(|u - su|, |v - sv|) <= radius.
+    fn rotated(&self) -> (isize, isize) {
+        (self.sx + self.sy, self.sx - self.sy)
+    }
 }
 
-impl Day15 {
-    pub fn load(filename: &str) -> Day15 {
-        let mut sensor_beacons: Vec<SensorBeacon> = Vec::new();
-        lazy_static! {
-            static ref SENSOR_BEACON_RE: Regex =
-                Regex::new("Sensor at x=(-?[0-9]+), y=(-?[0-9]+): closest beacon is at x=(-?[0-9]+), y=(-?[0-9]+)").unwrap();
-        }
+// How part 2 looks for the distress beacon.  See the registry for the names.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Method {
+    Perimeter,  // walk just outside each sensor's area
+    Lines,      // intersect the gaps between neighbouring areas
+    All,        // every uncovered cell, row by row
+}
 
-        let file = File::open(filename).unwrap();
-        let reader = BufReader::new(file);
+// The distress beacon is somewhere in 0..=SEARCH_MAX in both x and y.
+const SEARCH_MAX: isize = 4000000;
 
-        for line in reader.lines() {
-            let l = &line.unwrap();
-            let caps = SENSOR_BEACON_RE.captures(&l);
-            match caps {
-                Some(caps) => {
-                    let sx = caps[1].parse::<isize>().unwrap();
-                    let sy = caps[2].parse::<isize>().unwrap();
-                    let bx = caps[3].parse::<isize>().unwrap();
-                    let by = caps[4].parse::<isize>().unwrap();
-                    let sb = SensorBeacon { sx, sy, bx, by };
-                    sensor_beacons.push(sb);
-                }
-                None => {
-                    // println!("Nope: '{}'", l);
-                }
-            }
-        }
+// Most uncovered cells listed when finding them all.
+const LIST_LIMIT: usize = 20;
 
-        Day15 { sensor_beacons }
+// What a pixel of the coverage map shows, most important first.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Pixel {
+    Distress,               // the uncovered cell in the search area
+    Sensor,
+    Beacon,
+    Covered(usize, usize),  // (first sensor covering it, number covering it)
+    Uncovered,
+}
+
+// The part of the plane a coverage map shows, inclusive, and how many cells
+// across each pixel is.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct View {
+    x0: isize,
+    y0: isize,
+    x1: isize,
+    y1: isize,
+    scale: isize,
+}
+
+// Largest map drawn when the scale isn't given, in pixels or characters across.
+const MAX_IMAGE_SIZE: isize = 800;
+const MAX_TEXT_SIZE: isize = 100;
+
+pub struct Day15 {
+    sensor_beacons: Vec<SensorBeacon>,
+    method: Method,
+}
+
+impl Day15 {
+    // The x ranges covered on row y, one per sensor whose area reaches it.
+    fn coverage(&self, y: isize) -> IntervalSet {
+        self.sensor_beacons.iter()
+            .map(|sb| {
+                // get manhattan distance for this sensor/beacon pair.
+                let dist = (sb.sx-sb.bx).abs() + (sb.sy-sb.by).abs();
+                // extra_dist is < 0 if this sensor/beacon pair doesn't intersect y,
+                // which makes an empty range.
+                let extra_dist = dist - (y - sb.sy).abs();
+                (sb.sx - extra_dist, sb.sx + extra_dist)
+            })
+            .collect()
     }
 
+    // Number of positions on row y where there can't be a beacon.
     pub fn not_on_line(&self, y: isize) -> usize {
-        // for reference, we need a hashmap of all beacons on the line of interest
-        let mut beacons_on_line: HashSet<isize> = HashSet::new();
-        for sb in &self.sensor_beacons {
-            if sb.by == y {
-                beacons_on_line.insert(sb.bx);
-            }
-        }
-
-        // line of interest has an entry for every x value where (x,y) is inside coverage
-        // area of some sensor/beacon pair.
-        let mut line_of_interest: HashSet<isize> = HashSet::new();
-        for sb in &self.sensor_beacons {
-            // get manhattan distance for this sensor/beacon pair.
-            let dist = (sb.sx-sb.bx).abs() + (sb.sy-sb.by).abs();
-            // extra_dist is < 0 if this sensor/beacon pair doesn't intersect y
-            let extra_dist = dist - (y - sb.sy).abs();
-            if extra_dist >= 0 {
-                for dx in 0..=extra_dist {
-                    let x = sb.sx - dx;
-                    if !beacons_on_line.contains(&x) {
-                        line_of_interest.insert(x);
-                    }
-                    let x = sb.sx + dx;
-                    if !beacons_on_line.contains(&x) {
-                        line_of_interest.insert(x);
-                    }
-                }
-            }
-        }
+        let beacons_on_line: IntervalSet = self.sensor_beacons.iter()
+            .filter(|sb| sb.by == y)
+            .map(|sb| (sb.bx, sb.bx))
+            .collect();
 
-        // count how many points we identified
-        line_of_interest.len()
+        self.coverage(y).subtract(&beacons_on_line).len()
     }
 
     fn is_covered(&self, x: isize, y: isize) -> bool {
@@ -126,29 +138,297 @@ impl Day15 {
         }
 
         if found {
-            // println!("found {}, {}!", found_x, found_y);
             Some((found_x, found_y))
         }
         else {
             None
         }
     }
+
+    // Diagonal lines, as u = x + y or v = x - y values, running through gaps
+    // one cell wide between the areas of two sensors.  If there are none in
+    // one direction, every line just outside an area is used instead.
+    fn gap_lines(&self) -> (Vec<isize>, Vec<isize>) {
+        let mut gaps: (Vec<isize>, Vec<isize>) = (Vec::new(), Vec::new());
+        let mut outside: (Vec<isize>, Vec<isize>) = (Vec::new(), Vec::new());
+        for a in &self.sensor_beacons {
+            let (au, av) = a.rotated();
+            let r = a.radius() + 1;
+            outside.0.extend([au - r, au + r]);
+            outside.1.extend([av - r, av + r]);
+
+            for b in &self.sensor_beacons {
+                let (bu, bv) = b.rotated();
+                // a's line on the side facing b is also b's line facing a.
+                if bu - au == a.radius() + b.radius() + 2 {
+                    gaps.0.push(au + r);
+                }
+                if bv - av == a.radius() + b.radius() + 2 {
+                    gaps.1.push(av + r);
+                }
+            }
+        }
+
+        for (lines, fallback) in [(&mut gaps.0, outside.0), (&mut gaps.1, outside.1)] {
+            if lines.is_empty() {
+                *lines = fallback;
+            }
+            lines.sort();
+            lines.dedup();
+        }
+
+        gaps
+    }
+
+    // Find the uncovered cell where gap lines cross, or where they meet the
+    // edge of the search area, without walking any perimeters.  This finds a
+    // cell hemmed in by sensor areas, as the puzzle's is, in time depending
+    // only on the number of sensors.
+    fn find_uncovered_by_lines(&self, min: isize, max: isize) -> Option<(isize, isize)> {
+        let (us, vs) = self.gap_lines();
+
+        let mut candidates: Vec<(isize, isize)> = vec![(min, min), (min, max), (max, min), (max, max)];
+        for &u in &us {
+            for &v in &vs {
+                // x and y are only whole when u and v have the same parity.
+                if (u + v) % 2 == 0 {
+                    candidates.push(((u + v) / 2, (u - v) / 2));
+                }
+            }
+        }
+        for edge in [min, max] {
+            for &u in &us {
+                candidates.extend([(edge, u - edge), (u - edge, edge)]);
+            }
+            for &v in &vs {
+                candidates.extend([(edge, edge - v), (v + edge, edge)]);
+            }
+        }
+
+        candidates.into_iter()
+            .filter(|&(x, y)| x >= min && y >= min && x <= max && y <= max)
+            .find(|&(x, y)| !self.is_covered(x, y))
+    }
+
+    // The number of uncovered cells in the search area, found from the gaps
+    // in each row's coverage, and the first limit of them, row by row.
+    fn all_uncovered(&self, min: isize, max: isize, limit: usize) -> (usize, Vec<(isize, isize)>) {
+        let mut count = 0;
+        let mut first = Vec::new();
+        for y in min..=max {
+            let gaps = self.coverage(y).gaps(min, max);
+            count += gaps.len();
+            for &(low, high) in gaps.ranges() {
+                if first.len() < limit {
+                    first.extend((low..=high).take(limit - first.len()).map(|x| (x, y)));
+                }
+            }
+        }
+
+        (count, first)
+    }
+
+    // The map's view: window=X0,Y0,X1,Y1 (default: all the sensors' areas)
+    // and scale=N cells per pixel (default: whatever fits in max_size).
+    fn view(&self, options: &[(String, String)], max_size: isize) -> Result<View, String> {
+        let mut window = None;
+        let mut scale = None;
+        for (key, value) in options {
+            match key.as_str() {
+                "window" => {
+                    let corners: Vec<isize> = value.split(',')
+                        .map(|n| n.trim().parse::<isize>())
+                        .collect::<Result<_, _>>()
+                        .map_err(|_| format!("window should be X0,Y0,X1,Y1, not '{}'", value))?;
+                    match corners[..] {
+                        [x0, y0, x1, y1] if x0 <= x1 && y0 <= y1 => window = Some((x0, y0, x1, y1)),
+                        _ => return Err(format!("window should be X0,Y0,X1,Y1, not '{}'", value)),
+                    }
+                }
+                "scale" => match value.parse::<isize>() {
+                    Ok(n) if n > 0 => scale = Some(n),
+                    _ => return Err(format!("scale should be a positive number, not '{}'", value)),
+                },
+                "search" => {}
+                _ => return Err(format!("unknown export option '{}'", key)),
+            }
+        }
+
+        let (x0, y0, x1, y1) = match window {
+            Some(window) => window,
+            None => self.sensor_beacons.iter()
+                .map(|sb| (sb.sx - sb.radius(), sb.sy - sb.radius(), sb.sx + sb.radius(), sb.sy + sb.radius()))
+                .reduce(|a, b| (a.0.min(b.0), a.1.min(b.1), a.2.max(b.2), a.3.max(b.3)))
+                .ok_or("no sensors to draw")?,
+        };
+        let scale = scale.unwrap_or_else(|| {
+            let size = (x1 - x0 + 1).max(y1 - y0 + 1);
+            (size + max_size - 1) / max_size
+        });
+
+        Ok(View { x0, y0, x1, y1, scale })
+    }
+
+    // The coverage map, one pixel per scale x scale block of cells.  Each
+    // pixel shows the cell at its top left, so only as many cells are looked
+    // at as there are pixels, but sensors, beacons and the distress beacon
+    // are always marked wherever they fall.
+    fn coverage_map(&self, view: &View, distress: Option<(isize, isize)>) -> Grid<Pixel> {
+        let width = ((view.x1 - view.x0) / view.scale + 1) as usize;
+        let height = ((view.y1 - view.y0) / view.scale + 1) as usize;
+        let mut map = Grid::new(width, height, Pixel::Uncovered);
+
+        for (row, col) in map.positions().collect::<Vec<_>>() {
+            let x = view.x0 + col as isize * view.scale;
+            let y = view.y0 + row as isize * view.scale;
+            let mut covering = self.sensor_beacons.iter().enumerate()
+                .filter(|(_, sb)| (sb.sx-x).abs() + (sb.sy-y).abs() <= sb.radius())
+                .map(|(i, _)| i);
+            if let Some(first) = covering.next() {
+                map[(row, col)] = Pixel::Covered(first, 1 + covering.count());
+            }
+        }
+
+        let mut mark = |(x, y): (isize, isize), pixel: Pixel| {
+            if x >= view.x0 && y >= view.y0 && x <= view.x1 && y <= view.y1 {
+                let position = (((y - view.y0) / view.scale) as usize, ((x - view.x0) / view.scale) as usize);
+                map[position] = pixel;
+            }
+        };
+        for sb in &self.sensor_beacons {
+            mark((sb.bx, sb.by), Pixel::Beacon);
+        }
+        for sb in &self.sensor_beacons {
+            mark((sb.sx, sb.sy), Pixel::Sensor);
+        }
+        if let Some(cell) = distress {
+            mark(cell, Pixel::Distress);
+        }
+
+        map
+    }
+
+    fn tuning_frequency((x, y): (isize, isize)) -> i64 {
+        x as i64 * 4000000 + y as i64
+    }
 }
 
 impl Day for Day15 {
+    fn parse(input: &Input) -> Result<Day15, AocError> {
+        let mut sensor_beacons: Vec<SensorBeacon> = Vec::new();
+        lazy_static! {
+            static ref SENSOR_BEACON_RE: Regex =
+                Regex::new("Sensor at x=(-?[0-9]+), y=(-?[0-9]+): closest beacon is at x=(-?[0-9]+), y=(-?[0-9]+)").unwrap();
+        }
+
+        for line in input.lines() {
+            match SENSOR_BEACON_RE.captures(line.text) {
+                Some(caps) => {
+                    let sx = line.field(&caps, 1)?;
+                    let sy = line.field(&caps, 2)?;
+                    let bx = line.field(&caps, 3)?;
+                    let by = line.field(&caps, 4)?;
+                    let sb = SensorBeacon { sx, sy, bx, by };
+                    sensor_beacons.push(sb);
+                }
+                None => line.unmatched()?,
+            }
+        }
+
+        Ok(Day15 { sensor_beacons, method: Method::Perimeter })
+    }
+
+    // A coverage map, as text (.txt) or a PGM (.pgm, shaded by how many
+    // sensors cover each pixel) or PPM (.ppm, coloured by sensor) image.  The
+    // search=N option marks the distress beacon in 0..=N (default 4000000).
+    fn export(&self, format: &str, options: &[(String, String)]) -> Result<Vec<u8>, String> {
+        let max_size = if format == "txt" { MAX_TEXT_SIZE } else { MAX_IMAGE_SIZE };
+        let view = self.view(options, max_size)?;
+        let search = match options.iter().rev().find(|(key, _)| key == "search") {
+            Some((_, value)) => value.parse::<isize>().map_err(|_| format!("search should be a number, not '{}'", value))?,
+            None => SEARCH_MAX,
+        };
+        let map = self.coverage_map(&view, self.find_uncovered_by_lines(0, search));
+
+        // Sensors get colours from a small palette, in turn.
+        const PALETTE: [[u8; 3]; 6] =
+            [[70, 110, 160], [70, 140, 90], [150, 100, 60], [120, 80, 150], [60, 140, 140], [150, 80, 100]];
+        let header = |magic: &str| format!("{}\n{} {}\n255\n", magic, map.width(), map.height()).into_bytes();
+        let picture = match format {
+            "txt" => map.render(|pixel| match pixel {
+                Pixel::Distress => 'X',
+                Pixel::Sensor => 'S',
+                Pixel::Beacon => 'B',
+                Pixel::Covered(..) => '#',
+                Pixel::Uncovered => '.',
+            }).into_bytes(),
+            "pgm" => {
+                let mut bytes = header("P5");
+                bytes.extend(map.iter().map(|(_, pixel)| match pixel {
+                    Pixel::Distress | Pixel::Sensor | Pixel::Beacon => 255,
+                    Pixel::Covered(_, count) => (60 + 30 * count).min(220) as u8,
+                    Pixel::Uncovered => 0,
+                }));
+                bytes
+            }
+            "ppm" => {
+                let mut bytes = header("P6");
+                for (_, pixel) in map.iter() {
+                    bytes.extend(match pixel {
+                        Pixel::Distress => [255, 0, 0],
+                        Pixel::Sensor => [255, 255, 255],
+                        Pixel::Beacon => [255, 220, 0],
+                        Pixel::Covered(first, _) => PALETTE[first % PALETTE.len()],
+                        Pixel::Uncovered => [0, 0, 0],
+                    });
+                }
+                bytes
+            }
+            _ => return Err(format!("can't draw a coverage map as '{}', only txt, pgm or ppm", format)),
+        };
+
+        Ok(picture)
+    }
+
+    fn set_variant(&mut self, variant: &str) -> Result<(), String> {
+        self.method = match variant {
+            "perimeter" => Method::Perimeter,
+            "lines" => Method::Lines,
+            "all" => Method::All,
+            _ => return Err(format!("no variant '{}'", variant)),
+        };
+
+        Ok(())
+    }
+
     fn part1(&self) -> Answer {
         Answer::Number(self.not_on_line(2000000))
     }
 
     fn part2(&self) -> Answer {
-        match self.find_uncovered(0, 4000000) {
-            Some((x, y)) => {
-                let tuning_frequency = x*4000000+y;
-                Answer::Number(tuning_frequency as usize)
-            }
-            None => {
-                Answer::Number(0)
+        let found = match self.method {
+            Method::Perimeter => self.find_uncovered(0, SEARCH_MAX),
+            Method::Lines => self.find_uncovered_by_lines(0, SEARCH_MAX),
+            Method::All => {
+                // One frequency per line, so a single cell reads the same as
+                // the other methods' answer.
+                let (count, first) = self.all_uncovered(0, SEARCH_MAX, LIST_LIMIT);
+                if count == 0 {
+                    return Answer::None;
+                }
+                let mut lines: Vec<String> = first.into_iter()
+                    .map(|cell| Day15::tuning_frequency(cell).to_string())
+                    .collect();
+                if count > LIST_LIMIT {
+                    lines.push(format!("... {} uncovered cells in all", count));
+                }
+                return Answer::Message(lines.join("\n"));
             }
+        };
+
+        match found {
+            Some(cell) => Answer::Signed(Day15::tuning_frequency(cell)),
+            None => Answer::None,
         }
     }
 }
@@ -160,34 +440,133 @@ mod tests {
 
     #[test]
     fn test_load() {
-        let d = Day15::load("examples/day15_example1.txt");
+        let d = Day15::load("examples/day15_example1.txt").unwrap();
         assert_eq!(d.sensor_beacons.len(), 14);
     }
 
     #[test]
     fn test_not_on_line() {
-        let d = Day15::load("examples/day15_example1.txt");
+        let d = Day15::load("examples/day15_example1.txt").unwrap();
         let eliminated = d.not_on_line(10);
         assert_eq!(eliminated, 26);
     }
 
+    #[test]
+    fn test_coverage() {
+        let d = Day15::load("examples/day15_example1.txt").unwrap();
+        assert_eq!(d.coverage(10).ranges(), [(-2, 24)]);
+        assert_eq!(d.coverage(11).gaps(0, 20).ranges(), [(14, 14)]);
+
+        // Each position covered agrees with is_covered.
+        for y in 0..=20 {
+            let coverage = d.coverage(y);
+            for x in -10..=30 {
+                assert_eq!(coverage.contains_set(&IntervalSet::single(x, x)), d.is_covered(x, y), "({}, {})", x, y);
+            }
+        }
+    }
+
     #[test]
     fn test_part1() {
-        let d = Day15::load("examples/day15_example1.txt");
+        let d = Day15::load("examples/day15_example1.txt").unwrap();
         assert_eq!(d.part1(), Answer::Number(0));
     }
 
     #[test]
     fn test_is_covered() {
-        let d = Day15::load("examples/day15_example1.txt");
+        let d = Day15::load("examples/day15_example1.txt").unwrap();
         assert!(d.is_covered(1, 1));
         assert!(!d.is_covered(14, 11));
     }
 
     #[test]
     fn test_find_uncovered() {
-        let d = Day15::load("examples/day15_example1.txt");
+        let d = Day15::load("examples/day15_example1.txt").unwrap();
         let uncovered = d.find_uncovered(0, 20);
         assert_eq!(uncovered, Some((14, 11)) );
     }
+
+    #[test]
+    fn test_find_uncovered_by_lines() {
+        let d = Day15::load("examples/day15_example1.txt").unwrap();
+        assert_eq!(d.find_uncovered_by_lines(0, 20), Some((14, 11)));
+
+        // A cell in the corner, with nothing to hem it in on two sides.
+        let d = Day15::from_str("Sensor at x=2, y=2: closest beacon is at x=2, y=5").unwrap();
+        assert_eq!(d.find_uncovered_by_lines(0, 4), Some((0, 0)));
+    }
+
+    #[test]
+    fn test_all_uncovered() {
+        let d = Day15::load("examples/day15_example1.txt").unwrap();
+        assert_eq!(d.all_uncovered(0, 20, 10), (1, vec![(14, 11)]));
+
+        let d = Day15::from_str("Sensor at x=2, y=2: closest beacon is at x=2, y=5").unwrap();
+        let (count, cells) = d.all_uncovered(0, 4, 10);
+        assert_eq!(count, 4);
+        assert_eq!(cells, vec![(0, 0), (4, 0), (0, 4), (4, 4)]);
+        for cell in cells {
+            assert!(!d.is_covered(cell.0, cell.1));
+        }
+        assert_eq!(d.all_uncovered(0, 4, 3), (4, vec![(0, 0), (4, 0), (0, 4)]));
+        assert_eq!(d.all_uncovered(-1, 5, 0).0, 49 - 25);
+    }
+
+    fn options(text: &str) -> Vec<(String, String)> {
+        text.split_whitespace()
+            .map(|option| option.split_once('=').unwrap())
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_view() {
+        let d = Day15::load("examples/day15_example1.txt").unwrap();
+        // The sensor at (8, 7) reaches furthest: from x=-1 to 17, y=-2 to 16.
+        assert_eq!(d.view(&[], 100), Ok(View { x0: -8, y0: -10, x1: 28, y1: 26, scale: 1 }));
+        assert_eq!(d.view(&[], 10).unwrap().scale, 4);
+        assert_eq!(d.view(&options("window=0,0,4000000,4000000 scale=1000"), 10),
+                   Ok(View { x0: 0, y0: 0, x1: 4000000, y1: 4000000, scale: 1000 }));
+        assert!(d.view(&options("window=0,0,4"), 10).is_err());
+        assert!(d.view(&options("window=5,0,4,4"), 10).is_err());
+        assert!(d.view(&options("scale=0"), 10).is_err());
+        assert!(d.view(&options("zoom=2"), 10).is_err());
+    }
+
+    #[test]
+    fn test_export() {
+        let d = Day15::load("examples/day15_example1.txt").unwrap();
+        let text = d.export("txt", &options("window=-2,9,25,11 search=20")).unwrap();
+        assert_eq!(String::from_utf8(text).unwrap(),
+            ".#########################..\n\
+             ####B######################.\n\
+             ##S#############X###########\n");
+
+        // Scaled down, a 2x2 block per pixel.
+        let text = d.export("txt", &options("window=0,0,7,3 scale=2")).unwrap();
+        assert_eq!(String::from_utf8(text).unwrap(), "#S##\n####\n");
+
+        let image = d.export("pgm", &options("window=0,0,9,4")).unwrap();
+        assert!(image.starts_with(b"P5\n10 5\n255\n"));
+        assert_eq!(image.len(), 12 + 10 * 5);
+        let image = d.export("ppm", &[]).unwrap();
+        assert!(image.starts_with(b"P6\n37 37\n255\n"));
+        assert_eq!(image.len(), 13 + 37 * 37 * 3);
+
+        assert!(d.export("png", &[]).is_err());
+        assert!(d.export("txt", &options("search=lots")).is_err());
+    }
+
+    #[test]
+    fn test_variants() {
+        let mut d = Day15::load("examples/day15_example1.txt").unwrap();
+        d.set_variant("lines").unwrap();
+        assert_eq!(d.method, Method::Lines);
+        d.set_variant("all").unwrap();
+        assert_eq!(d.method, Method::All);
+        d.set_variant("perimeter").unwrap();
+        assert_eq!(d.method, Method::Perimeter);
+        assert!(d.set_variant("Lines").is_err());
+        assert_eq!(d.method, Method::Perimeter);
+    }
 }
@@ -1,11 +1,11 @@
+use crate::branch_bound::{self, BranchAndBound};
 use crate::day::{Day, Answer};
+use crate::error::AocError;
+use crate::input::Input;
+use crate::observer::Stats;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::collections::VecDeque;
-use std::rc::Rc;
-use priority_queue::PriorityQueue;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
 use std::hash::Hash;
 
 use lazy_static::lazy_static;
@@ -20,47 +20,63 @@ struct ValveInfo {
     neighbors: Vec<usize>,
 }
 
+// How the best plan is found.  See the registry for the names.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Method {
+    BranchBound,    // extend plans a valve at a time, most promising first
+    Subsets,        // the best plan for each set of valves, then split them up
+}
+
+// How optimistic branch and bound is about what a plan could still release.
+// See the registry for the names.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Bound {
+    Greedy,     // a valve every 2 minutes, biggest first, wherever they are
+    Distance,   // each valve as soon as the nearest agent could open it
+    Tightest,   // whichever of the two is lower
+}
+
 // Problem represents the overall problem to be solved.  It consists of the ValveInfo above plus time and num agents.
-struct Problem<'a> {
+pub struct Problem<'a> {
     period: usize,  // how long we can take
-    two_agents: bool, // true if two agents are active
+    agents: usize,  // how many agents are opening valves
     valves: &'a HashMap<usize, ValveInfo>,  // what valves exist and how they are connected.
     start_position: usize,  // What position the agents start at.
     distance: HashMap<(usize, usize), usize>,  // distance[(a,b)] = Number of moves from a to b.
+    bound: Bound,  // how max_uncaptured is estimated
 }
 
+// One minute of an agent's plan.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Action {
+    Move(usize),    // through a tunnel to this valve
+    Open(usize),
+}
+
+// Colours of each agent's route, in turn, when the tunnels are exported.
+const ROUTE_COLOURS: [&str; 6] = ["red", "blue", "darkgreen", "orange", "purple", "brown"];
+
 // This represents a solution in-progress.  It stores the actions taken at each time step by each agent.
 #[derive(Hash, PartialEq, Eq, Clone)]
-struct Solution {
+pub struct Solution {
     opens: Vec<Vec<usize>>,   // opens[agent][n] is the nth valve opened by agent.
+    retired: Vec<bool>,       // retired[agent] is true once agent has stopped opening valves.
     flow_captured : usize,
     max_uncaptured : usize,
 }
 
-// Note: The Solver will keep a best potential score for each state observed.  
-// If a state is re-visited with a lower potential score, that move will be pruned.
-
-// Solutions will be explored by the Solver by the following process:
-//   Take the next solution-in-progress from the priority queue.
-//   If the solution-in-progress is complete, 
-//     declare it as the optimal solution.
-//   Else
-//     Generate all valid next-step solutions.
-//     For each next step.
-//       Discard the potential solution if its max potential is lower than best actual
-//       Store the extended solution using the max potential score as its priority.
-
-struct Solver<'a> {
-    problem: &'a Problem<'a>,
-}
+// Solutions are explored by branch and bound (see branch_bound.rs).  A
+// solution's upper bound is the flow it has captured plus max_uncaptured, an
+// optimistic estimate of what opening the remaining valves could add, made
+// the problem's way (see Bound).  The tighter the estimate, the fewer plans
+// have to be looked at.
 
 impl<'a> Problem<'a> {
-    pub fn new(period: usize, two_agents: bool, valves: &'a HashMap<usize, ValveInfo>, start_position: usize) -> Problem {
+    fn new(period: usize, agents: usize, valves: &'a HashMap<usize, ValveInfo>, start_position: usize) -> Problem<'a> {
 
         // Compute distances from any valve to any valve.
         let mut distance: HashMap<(usize, usize), usize> = HashMap::new();
         for from in valves.keys() {
-            // println!("Finding distances from {}", from);
             let mut visited: HashSet<usize> = HashSet::new();
             let mut to_visit: VecDeque<(usize, usize)> = VecDeque::new();  // FIFO
             to_visit.push_back((*from, 0));
@@ -68,13 +84,12 @@ impl<'a> Problem<'a> {
                 let (to, d) = to_visit.pop_front().unwrap();
                 if !visited.contains(&to) {
                     // From <from> we reached <to> for the first time.
-                    // println!("    {} to {} is {}", *from, to, d);
                     distance.insert((*from, to), d);
                     visited.insert(to);
 
                     // Push all potential next steps
                     for next_to in &valves[&to].neighbors {
-                        if !visited.contains(&next_to) {
+                        if !visited.contains(next_to) {
                             to_visit.push_back((*next_to, d+1));
                         }
                     }
@@ -83,17 +98,18 @@ impl<'a> Problem<'a> {
         }
 
         Problem {
-            period: period,
-            two_agents: two_agents,
-            valves: valves,
-            start_position: start_position,
-            distance: distance,
+            period,
+            agents,
+            valves,
+            start_position,
+            distance,
+            bound: Bound::Tightest,
         }
     }
 
     // Get the "empty" solution, representing the start state of the puzzle.
     pub fn get_start(&self) -> Solution {
-        Solution::new(&self)
+        Solution::new(self)
     }
 
     pub fn ttg(&self, path: &Vec<usize>) -> usize {
@@ -118,19 +134,11 @@ impl Solution {
 
     fn new(problem: &Problem) -> Solution {
         // create empty action vectors for all actors
-        let mut opens: Vec<Vec<usize>> = Vec::new();
-
-        // First agent's list of opens is empty.
-        opens.push(Vec::new());
-
-        if problem.two_agents {
-            // Second agent's list of opens is empty.
-            opens.push(Vec::new());
-        }
+        let opens: Vec<Vec<usize>> = vec![Vec::new(); problem.agents];
 
         // Construct a vector of all the flow rates available
         let mut flows: Vec<usize> = Vec::new();
-        for (_valve_id, valve_info) in problem.valves {
+        for valve_info in problem.valves.values() {
             if valve_info.flow_rate > 0 {
                 flows.push(valve_info.flow_rate);
             }
@@ -144,16 +152,12 @@ impl Solution {
         let mut ttg = problem.period;
         let mut i = 0;
         while ttg >= 2 && i < flows.len() {
-            // First agent opens the biggest available valve
-            let rate = flows[i];
-            i += 1;
-            max_uncaptured += (ttg-2)*rate;
-
-            if problem.two_agents && i < flows.len() {
-                // Second agent opens the next biggest available valve
-                let rate = flows[i];
-                i += 1;
-                max_uncaptured += (ttg-2)*rate;
+            // Each agent in turn opens the biggest available valve
+            for _ in 0..problem.agents {
+                if i < flows.len() {
+                    max_uncaptured += (ttg-2)*flows[i];
+                    i += 1;
+                }
             }
 
             // two time steps pass as we open the valve then move at least one
@@ -166,13 +170,21 @@ impl Solution {
             }
         }
 
-        Solution { opens, flow_captured: 0, max_uncaptured }
+        let retired = vec![false; problem.agents];
+        let mut solution = Solution { opens, retired, flow_captured: 0, max_uncaptured };
+        solution.max_uncaptured = match problem.bound {
+            Bound::Greedy => max_uncaptured,
+            Bound::Distance => solution.distance_uncaptured(problem),
+            Bound::Tightest => max_uncaptured.min(solution.distance_uncaptured(problem)),
+        };
+
+        solution
     }
     
     // A solution is complete when its action sequence is as long as self.period allows.
     fn is_complete(&self, problem: &Problem) -> bool {
-        for sequence in &self.opens {
-            let ttg = problem.ttg(sequence);
+        for agent_id in 0..self.opens.len() {
+            let ttg = self.ttg(agent_id, problem);
             if ttg > 0 {
                 return false;
             }
@@ -209,11 +221,16 @@ impl Solution {
             }
         }
 
+        // With nobody to move there is nothing to do.
+        if self.opens.is_empty() {
+            return nexts;
+        }
+
         // Figure out which agent to "move".  (The one with most ttg or lowest index)
         let mut agent_id = 0;
-        let mut agent_ttg = problem.ttg(&self.opens[agent_id]);
+        let mut agent_ttg = self.ttg(agent_id, problem);
         for other_id in 1..self.opens.len() {
-            let other_ttg = problem.ttg(&self.opens[other_id]);
+            let other_ttg = self.ttg(other_id, problem);
             if other_ttg > agent_ttg {
                 agent_id = other_id;
                 agent_ttg = other_ttg;
@@ -228,24 +245,143 @@ impl Solution {
 
                 nexts.push(next_solution);
             }
+
+            // Or it could leave the remaining valves to the others, if any of
+            // them have time left.
+            if (0..self.opens.len()).any(|other_id| other_id != agent_id && self.ttg(other_id, problem) > 0) {
+                let mut next_solution = self.clone();
+                next_solution.retired[agent_id] = true;
+                next_solution.max_uncaptured = next_solution.estimate_uncaptured(problem);
+
+                nexts.push(next_solution);
+            }
         }
 
         nexts
     }
 
+    // Who does what each minute, in the words of the puzzle, with the
+    // pressure released so far.  You are the first agent and the elephant
+    // the second.
+    pub fn itinerary(&self, problem: &Problem) -> String {
+        let name = |valve_id: &usize| problem.valves[valve_id].name.as_str();
+
+        // actions[agent][minute-1] is what agent does that minute.
+        let mut actions: Vec<Vec<String>> = Vec::new();
+        for agent_id in 0..self.opens.len() {
+            let (who, s) = match agent_id {
+                0 => ("You".to_string(), ""),
+                1 => ("The elephant".to_string(), "s"),
+                _ => (format!("Elephant {}", agent_id), "s"),
+            };
+            actions.push(self.actions(agent_id, problem).iter().map(|action| match action {
+                Action::Move(valve_id) => format!("{} move{} to valve {}.", who, s, name(valve_id)),
+                Action::Open(valve_id) => format!("{} open{} valve {}.", who, s, name(valve_id)),
+            }).collect());
+        }
+        let opened = self.opened(problem);
+
+        let mut released = 0;
+        let mut minutes: Vec<String> = Vec::new();
+        for minute in 1..=problem.period {
+            let mut open: Vec<&usize> = opened.iter()
+                .filter(|(_, (_, opened_at))| *opened_at < minute)
+                .map(|(valve_id, _)| valve_id)
+                .collect();
+            open.sort_by_key(|valve_id| name(valve_id));
+            let rate: usize = open.iter().map(|valve_id| problem.valves[valve_id].flow_rate).sum();
+            released += rate;
+
+            let names: Vec<&str> = open.iter().map(|valve_id| name(valve_id)).collect();
+            let mut lines = vec![format!("== Minute {} ==", minute)];
+            lines.push(match names.len() {
+                0 => "No valves are open.".to_string(),
+                1 => format!("Valve {} is open, releasing {} pressure ({} in all).", names[0], rate, released),
+                2 => format!("Valves {} and {} are open, releasing {} pressure ({} in all).", names[0], names[1], rate, released),
+                n => format!("Valves {}, and {} are open, releasing {} pressure ({} in all).",
+                             names[..n-1].join(", "), names[n-1], rate, released),
+            });
+            for agent_actions in &actions {
+                if let Some(action) = agent_actions.get(minute - 1) {
+                    lines.push(action.clone());
+                }
+            }
+            minutes.push(lines.join("\n"));
+        }
+
+        minutes.join("\n\n")
+    }
+
+    // What agent does each minute, following a shortest path to each valve
+    // it opens, until time runs out.
+    fn actions(&self, agent_id: usize, problem: &Problem) -> Vec<Action> {
+        let mut actions: Vec<Action> = Vec::new();
+        let mut position = problem.start_position;
+        for valve_id in &self.opens[agent_id] {
+            while position != *valve_id {
+                let to_go = problem.distance[&(position, *valve_id)];
+                position = *problem.valves[&position].neighbors.iter()
+                    .find(|n| problem.distance[&(**n, *valve_id)] + 1 == to_go)
+                    .unwrap();
+                actions.push(Action::Move(position));
+            }
+            actions.push(Action::Open(*valve_id));
+        }
+        actions.truncate(problem.period);
+
+        actions
+    }
+
+    // opened[valve_id] = (the agent that opened it, the minute it did so).
+    fn opened(&self, problem: &Problem) -> HashMap<usize, (usize, usize)> {
+        let mut opened: HashMap<usize, (usize, usize)> = HashMap::new();
+        for agent_id in 0..self.opens.len() {
+            for (minute, action) in self.actions(agent_id, problem).iter().enumerate() {
+                if let Action::Open(valve_id) = action {
+                    opened.insert(*valve_id, (agent_id, minute + 1));
+                }
+            }
+        }
+
+        opened
+    }
+
+    // Time agent has left to open valves.
+    fn ttg(&self, agent_id: usize, problem: &Problem) -> usize {
+        if self.retired[agent_id] {
+            0
+        }
+        else {
+            problem.ttg(&self.opens[agent_id])
+        }
+    }
+
     fn update(&mut self, agent_id: usize, valve_id: usize, problem: &Problem) {
         // Append a new valve id to the agent's opens vector.
         self.opens[agent_id].push(valve_id);
-        // println!("Opening valve {:?}", self.opens[agent_id]);
         let ttg = problem.ttg(&self.opens[agent_id]);
         if ttg > 0 {
-            // println!("Capturing flow {} for ttg {}.", problem.valves[&valve_id].flow_rate, ttg);
             self.flow_captured += (ttg) * problem.valves[&valve_id].flow_rate;
         }
 
+        self.max_uncaptured = self.estimate_uncaptured(problem);
+    }
+
+    // Optimistically, what could the agents still release?
+    fn estimate_uncaptured(&self, problem: &Problem) -> usize {
+        match problem.bound {
+            Bound::Greedy => self.greedy_uncaptured(problem),
+            Bound::Distance => self.distance_uncaptured(problem),
+            Bound::Tightest => self.greedy_uncaptured(problem).min(self.distance_uncaptured(problem)),
+        }
+    }
+
+    // Each agent opens the biggest closed valve left whenever it is free,
+    // taking 2 minutes to get to the next, however far it really is.
+    fn greedy_uncaptured(&self, problem: &Problem) -> usize {
         // Construct a vector of all the flow rates available
         let mut flows: Vec<usize> = Vec::new();
-        for (_valve_id, valve_info) in problem.valves {
+        for valve_info in problem.valves.values() {
             if valve_info.flow_rate > 0 {
                 flows.push(valve_info.flow_rate);
             }
@@ -258,98 +394,372 @@ impl Solution {
 
         let mut agent_ttg: Vec<usize> = Vec::new();
         for agent_id in 0..self.opens.len() {
-            agent_ttg.push(problem.ttg(&self.opens[agent_id]));
+            agent_ttg.push(self.ttg(agent_id, problem));
         }
         for sim_time in 0..problem.period {
             let sim_ttg = problem.period - sim_time;
 
-            for agent_id in 0..self.opens.len() {
-                if sim_ttg == agent_ttg[agent_id] && next_flow < flows.len() {
+            for ttg in agent_ttg.iter_mut() {
+                if sim_ttg == *ttg && next_flow < flows.len() {
                     // This agent could take the highest available flow rate
                     // then be occupied for two time steps
                     max_uncaptured += flows[next_flow]*(sim_ttg);
                     next_flow += 1;
-                    if agent_ttg[agent_id] >= 2 {
-                        agent_ttg[agent_id] -= 2;
+                    if *ttg >= 2 {
+                        *ttg -= 2;
                     }
                     else {
-                        agent_ttg[agent_id] = 0;
+                        *ttg = 0;
                     }
                 }
             }
         }
 
-        self.max_uncaptured = max_uncaptured;
-        
+        max_uncaptured
+    }
+
+    // Each closed valve is opened as soon as whichever agent could get there
+    // first can open it, as if they had nothing else to do.
+    fn distance_uncaptured(&self, problem: &Problem) -> usize {
+        // (where each agent is, time it has to go)
+        let agents: Vec<(usize, usize)> = (0..self.opens.len())
+            .map(|agent_id| (*self.opens[agent_id].last().unwrap_or(&problem.start_position), self.ttg(agent_id, problem)))
+            .filter(|(_, ttg)| *ttg > 0)
+            .collect();
+
+        let mut max_uncaptured = 0;
+        for valve_info in problem.valves.values() {
+            if valve_info.flow_rate == 0 || self.opens.iter().any(|opens| opens.contains(&valve_info.id)) {
+                continue;
+            }
+            let ttg = agents.iter()
+                .map(|(position, ttg)| ttg.saturating_sub(problem.distance[&(*position, valve_info.id)] + 1))
+                .max()
+                .unwrap_or(0);
+            max_uncaptured += ttg * valve_info.flow_rate;
+        }
+
+        max_uncaptured
     }
 }
 
-// TODO: Create Trait to encapsulate Problem
-// TODO: Make Generic to Problem Trait.
-impl<'a> Solver<'a> {
+impl<'a> branch_bound::Problem for Problem<'a> {
+    type State = Solution;
 
-    pub fn solve(&self) -> Option<Rc<Solution>> {
-        let mut in_progress: PriorityQueue<Rc<Solution>, usize> = PriorityQueue::new();
+    fn start(&self) -> Solution {
+        self.get_start()
+    }
 
-        // Seed the in_progress queue and best_score map with the initial state
-        let start = Rc::new(self.problem.get_start());
-        in_progress.push(start.clone(), 1);  // priority doesn't matter for initial push.
+    fn score(&self, soln: &Solution) -> usize {
+        soln.flow_captured
+    }
 
-        let mut best_solution = start.clone();
+    fn upper_bound(&self, soln: &Solution) -> usize {
+        soln.flow_captured + soln.max_uncaptured
+    }
+
+    fn is_complete(&self, soln: &Solution) -> bool {
+        soln.is_complete(self)
+    }
+
+    fn successors(&self, soln: &Solution) -> Vec<Solution> {
+        soln.get_next_steps(self)
+    }
+}
 
-        // Loop through in_progress queue until we get a solution or it goes empty
-        // (When we get a solution, it will be the one with the highest potential score)
-        while let Some((soln, _priority)) = in_progress.pop() {
+// The subsets solver works on just the valves worth opening, numbered from 0
+// so that any set of them fits in a ValveSet.  Whatever order one agent opens
+// a set of valves in, only the best order matters, so it finds the most each
+// set can release with one agent, and then how best to share the valves out
+// between the agents.  That takes memory for every subset, and time for every
+// subset of every subset, which is fine for the 15 or so valves in a puzzle
+// input but soon isn't for many more, so the dp variant refuses inputs with
+// more than MAX_SUBSET_VALVES.
 
-            // Evaluate this solution's potential
-            let potential = soln.flow_captured + soln.max_uncaptured;
+type ValveSet = u64;
 
-            // Report in_progress queue size, ttg, flowed, potential
-            // println!("Depth: {}, captured: {}, Potential: {}, Best: {}", 
-            //     in_progress.len(),
-            //     soln.flow_captured, 
-            //     potential,
-            //     best_solution.flow_captured);
-            // println!("    Soln: {:?}", soln.opens[0]);
+const MAX_SUBSET_VALVES: usize = 16;
 
-            // Reject this solution if we already have a better one.
-            if potential <= best_solution.flow_captured { continue; }
+struct Compressed {
+    valves: Vec<usize>,         // valves[i] is the id of the ith valve with a flow
+    flow_rate: Vec<usize>,
+    distance: Vec<Vec<usize>>,  // distance[i][j] = Number of moves from valve i to valve j.
+    from_start: Vec<usize>,     // from_start[i] = Number of moves from the start to valve i.
+}
+
+impl Compressed {
+    fn new(problem: &Problem) -> Compressed {
+        let mut valves: Vec<usize> = problem.valves.values()
+            .filter(|v| v.flow_rate > 0)
+            .map(|v| v.id)
+            .collect();
+        assert!(valves.len() <= MAX_SUBSET_VALVES, "too many valves for the subsets method");
+        valves.sort();
+        let flow_rate = valves.iter().map(|id| problem.valves[id].flow_rate).collect();
+        let distance = valves.iter()
+            .map(|a| valves.iter().map(|b| problem.distance[&(*a, *b)]).collect())
+            .collect();
+        let from_start = valves.iter().map(|id| problem.distance[&(problem.start_position, *id)]).collect();
+
+        Compressed { valves, flow_rate, distance, from_start }
+    }
+
+    // For every set of valves, the most one agent can release by opening
+    // exactly those, and the order to open them in.
+    fn best_orders(&self, period: usize) -> Vec<(usize, Vec<usize>)> {
+        let mut best: Vec<(usize, Vec<usize>)> = vec![(0, Vec::new()); 1 << self.valves.len()];
+
+        // (where we are, time to go, valves opened, flow released, order opened)
+        let mut to_visit = vec![(None::<usize>, period, 0 as ValveSet, 0, Vec::new())];
+        while let Some((position, ttg, opened, flow, order)) = to_visit.pop() {
+            if flow > best[opened as usize].0 {
+                best[opened as usize] = (flow, order.clone());
+            }
+            for next in 0..self.valves.len() {
+                if opened & (1 << next) != 0 {
+                    continue;
+                }
+                let move_dist = match position {
+                    Some(p) => self.distance[p][next],
+                    None => self.from_start[next],
+                };
+                // Opening a valve in the last minute releases nothing.
+                if move_dist + 1 < ttg {
+                    let next_ttg = ttg - move_dist - 1;
+                    let mut next_order = order.clone();
+                    next_order.push(next);
+                    to_visit.push((Some(next), next_ttg, opened | 1 << next, flow + next_ttg*self.flow_rate[next], next_order));
+                }
+            }
+        }
 
-            // Is this solution better than the best so far?
-            if soln.flow_captured > best_solution.flow_captured {
-                // println!("New best: {:?}", soln.flow_captured);
-                best_solution = soln.clone();
+        best
+    }
 
-                // TODO? Delete entries from in_progress that have less potential than this new find.
+    // The best plan for the problem's agents, as the valves each opens in order.
+    fn solve(&self, problem: &Problem) -> Vec<Vec<usize>> {
+        let best = self.best_orders(problem.period);
+        let all = (1 << self.valves.len()) - 1;
+
+        // within[set] = (the most one agent can release opening only valves in
+        // set, the valves it opens to do so).
+        let mut within: Vec<(usize, ValveSet)> = best.iter().enumerate()
+            .map(|(set, (flow, _))| (*flow, set as ValveSet))
+            .collect();
+        for bit in 0..self.valves.len() {
+            for set in 0..within.len() {
+                if set & (1 << bit) != 0 && within[set ^ (1 << bit)].0 > within[set].0 {
+                    within[set] = within[set ^ (1 << bit)];
+                }
             }
+        }
 
-            // If this solution isn't complete
-            if !soln.is_complete(self.problem) {
-                // For each next step
-                for next in soln.get_next_steps(self.problem) {
-                    // Reject if we already have a better solution
-                    let potential = next.flow_captured + next.max_uncaptured;
-                    if potential < best_solution.flow_captured { continue; }
-                        
-                    // Push this solution
-                    let priority = potential;
-                    in_progress.push(Rc::new(next), priority);
+        // shares[n][set] = (the most n+2 agents can release opening only
+        // valves in set, the valves given to the last of them).  Each agent
+        // added tries every split of each set into the valves it may open and
+        // the rest, which for two agents is every pair of disjoint sets.
+        let mut shares: Vec<Vec<(usize, ValveSet)>> = Vec::new();
+        for n in 1..problem.agents {
+            let mut share: Vec<(usize, ValveSet)> = vec![(0, 0); within.len()];
+            for set in 0..=all {
+                // Walk the subsets of set, largest first.
+                let mut mine = set;
+                loop {
+                    let others = match n {
+                        1 => within[(set ^ mine) as usize].0,
+                        _ => shares[n - 2][(set ^ mine) as usize].0,
+                    };
+                    let flow = others + within[mine as usize].0;
+                    if flow > share[set as usize].0 {
+                        share[set as usize] = (flow, mine);
+                    }
+                    if mine == 0 {
+                        break;
+                    }
+                    mine = (mine - 1) & set;
                 }
             }
+            shares.push(share);
         }
 
-        Some(best_solution)
-        
+        // Hand out the valves, last agent first.
+        let mut opens: Vec<Vec<usize>> = vec![Vec::new(); problem.agents];
+        let mut remaining = all;
+        for agent in (0..problem.agents).rev() {
+            let mine = match agent {
+                0 => remaining,
+                _ => shares[agent - 1][remaining as usize].1,
+            };
+            let opened = within[mine as usize].1;
+            opens[agent] = best[opened as usize].1.iter().map(|i| self.valves[*i]).collect();
+            remaining ^= mine;
+        }
+
+        opens
     }
 }
 
-
 pub struct Day16 {
     valve_ids: HashMap<String, usize>,
     valves: HashMap<usize, ValveInfo>,
+    method: Method,
+    bound: Bound,   // for branch and bound
 }
 
 impl Day16 {
+    // The most pressure that agents working together can release in the
+    // given minutes, all starting from the named valve, or None if there is
+    // no such valve.  The parts go through problem and solve instead, to
+    // explain their answers; --export's plan and start options come here.
+    pub fn optimise(&self, agents: usize, minutes: usize, start: &str) -> Option<Solution> {
+        let problem = self.problem(agents, minutes, start)?;
+
+        Some(self.solve(&problem))
+    }
+
+    pub fn problem(&self, agents: usize, minutes: usize, start: &str) -> Option<Problem<'_>> {
+        let start_position = self.valve_ids.get(start)?;
+        let mut problem = Problem::new(minutes, agents, &self.valves, *start_position);
+        problem.bound = self.bound;
+
+        Some(problem)
+    }
+
+    pub fn solve(&self, problem: &Problem) -> Solution {
+        self.search(problem).0
+    }
+
+    // The best plan, and what branch and bound did to find it if that was
+    // the method used.
+    pub fn search(&self, problem: &Problem) -> (Solution, Option<Stats>) {
+        match self.method {
+            Method::BranchBound => {
+                let mut solver = BranchAndBound::new(problem);
+                let solution = solver.solve();
+                (solution, Some(solver.stats().clone()))
+            }
+            Method::Subsets => {
+                let mut solution = problem.get_start();
+                for (agent_id, opens) in Compressed::new(problem).solve(problem).into_iter().enumerate() {
+                    for valve_id in opens {
+                        solution.update(agent_id, valve_id, problem);
+                    }
+                }
+                (solution, None)
+            }
+        }
+    }
+
+    // The tunnels as a Graphviz graph or, when compressed, just the start
+    // and the valves worth opening, joined by the number of moves between
+    // them.  Each agent's route in plan, if there is one, is drawn in its own
+    // colour, the valves it opens are marked with the minute it opens them,
+    // and the graph is labelled with the pressure released.
+    fn to_dot(&self, problem: &Problem, compressed: bool, plan: Option<&Solution>) -> String {
+        let name = |valve_id: &usize| self.valves[valve_id].name.as_str();
+        let mut ids: Vec<usize> = self.valves.values()
+            .filter(|v| !compressed || v.flow_rate > 0 || v.id == problem.start_position)
+            .map(|v| v.id)
+            .collect();
+        ids.sort_by_key(|valve_id| name(valve_id));
+
+        // travelled[(a, b)] = the agents that go between a and b, either way
+        // and however often, with a's name first.
+        let mut travelled: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+        let mut opened: HashMap<usize, (usize, usize)> = HashMap::new();
+        if let Some(solution) = plan {
+            opened = solution.opened(problem);
+            for agent_id in 0..solution.opens.len() {
+                let mut position = problem.start_position;
+                for action in solution.actions(agent_id, problem) {
+                    let next = match (action, compressed) {
+                        (Action::Move(valve_id), false) | (Action::Open(valve_id), true) => valve_id,
+                        _ => continue,
+                    };
+                    let edge = if name(&position) < name(&next) { (position, next) } else { (next, position) };
+                    let agents = travelled.entry(edge).or_default();
+                    if !agents.contains(&agent_id) {
+                        agents.push(agent_id);
+                    }
+                    position = next;
+                }
+            }
+        }
+        let colours = |agents: &[usize]| -> String {
+            agents.iter().map(|agent_id| ROUTE_COLOURS[agent_id % ROUTE_COLOURS.len()]).collect::<Vec<&str>>().join(":")
+        };
+
+        let mut lines: Vec<String> = Vec::new();
+        lines.push(format!("graph {} {{", if compressed { "valves" } else { "tunnels" }));
+        lines.push("    node [shape=circle];".to_string());
+        if let Some(plan) = plan {
+            lines.push(format!("    label=\"{} agents, {} minutes from {}: {} released\";",
+                               problem.agents, problem.period, self.valves[&problem.start_position].name, plan.flow_captured));
+        }
+        for valve_id in &ids {
+            let valve = &self.valves[valve_id];
+            let mut label = valve.name.clone();
+            let mut attributes: Vec<String> = Vec::new();
+            if valve.flow_rate > 0 {
+                label += &format!("\\n{}", valve.flow_rate);
+                attributes.push("shape=doublecircle".to_string());
+            }
+            if *valve_id == problem.start_position {
+                attributes.push("style=bold".to_string());
+            }
+            if let Some((agent_id, minute)) = opened.get(valve_id) {
+                label += &format!("\\nopened {}", minute);
+                attributes.push(format!("color=\"{}\"", colours(&[*agent_id])));
+            }
+            attributes.insert(0, format!("label=\"{}\"", label));
+            lines.push(format!("    {} [{}];", valve.name, attributes.join(", ")));
+        }
+
+        for (i, a) in ids.iter().enumerate() {
+            for b in &ids[i+1..] {
+                let mut attributes: Vec<String> = Vec::new();
+                if compressed {
+                    attributes.push(format!("label=\"{}\"", problem.distance[&(*a, *b)]));
+                }
+                else if !self.valves[a].neighbors.contains(b) && !self.valves[b].neighbors.contains(a) {
+                    continue;
+                }
+                if let Some(agents) = travelled.get(&(*a, *b)) {
+                    attributes.push(format!("color=\"{}\"", colours(agents)));
+                    attributes.push("penwidth=3".to_string());
+                }
+                if attributes.is_empty() {
+                    lines.push(format!("    {} -- {};", name(a), name(b)));
+                }
+                else {
+                    lines.push(format!("    {} -- {} [{}];", name(a), name(b), attributes.join(", ")));
+                }
+            }
+        }
+        lines.push("}".to_string());
+
+        lines.join("\n") + "\n"
+    }
+
+    // The answer, and the plan that releases it.
+    fn answer(&self, agents: usize, minutes: usize) -> Answer {
+        let problem = self.problem(agents, minutes, "AA").unwrap();
+        let (solution, stats) = self.search(&problem);
+
+        let mut detail = solution.itinerary(&problem);
+        if let Some(stats) = stats {
+            let bound = match problem.bound {
+                Bound::Greedy => "greedy",
+                Bound::Distance => "distance",
+                Bound::Tightest => "tightest",
+            };
+            detail += &format!("\n\nBranch and bound with the {} bound expanded {} states, generated {} and pruned {}.",
+                               bound, stats.expanded, stats.generated, stats.pruned);
+        }
+
+        Answer::Detailed { answer: Box::new(Answer::Number(solution.flow_captured)), detail }
+    }
+
     fn get_id(&mut self, name: &str) -> usize {
         if self.valve_ids.contains_key(name) {
             *self.valve_ids.get(name).unwrap()
@@ -372,89 +782,131 @@ impl Day16 {
 
         self.valves.insert(valve_id, valve_info);
     }
+}
 
-    pub fn load(filename: &str) -> Day16 {
-        let mut d = Day16 { valve_ids: HashMap::new(), valves: HashMap::new() };
+impl Day for Day16 {
+    fn parse(input: &Input) -> Result<Day16, AocError> {
+        let mut d = Day16 { valve_ids: HashMap::new(), valves: HashMap::new(),
+                           method: Method::BranchBound, bound: Bound::Tightest };
         lazy_static! {
             static ref LINE_RE: Regex =
                 Regex::new("Valve ([A-Z]+) has flow rate=([0-9]+); tunnels? leads? to valves? ([A-Z, ]+)").unwrap();
         }
 
-        let file = File::open(filename).unwrap();
-        let reader = BufReader::new(file);
-
-        for line in reader.lines() {
-            let l = &line.unwrap();
-            let caps = LINE_RE.captures(&l);
-            match caps {
+        for line in input.lines() {
+            match LINE_RE.captures(line.text) {
                 Some(caps) => {
                     let valve_name = &caps[1];
-                    let flow_rate = caps[2].parse::<usize>().unwrap();
+                    let flow_rate = line.field(&caps, 2)?;
                     let neighbors = caps[3].split(", ").collect();
 
                     d.add_valve(valve_name, flow_rate, neighbors);
                 }
-                None => {}
+                None => line.unmatched()?,
             }
         }
 
-        d
-    }
-}
+        // Every tunnel must lead to a valve we know about, and we start at AA.
+        for (name, id) in &d.valve_ids {
+            if !d.valves.contains_key(id) {
+                return Err(input.invalid(&format!("tunnel leads to undescribed valve {}", name)));
+            }
+        }
+        if !d.valve_ids.contains_key("AA") {
+            return Err(input.invalid("there is no valve AA to start from"));
+        }
 
-impl<'a> Day for Day16 {
-    fn part1(&self) -> Answer {
-        let start_position = self.valve_ids.get("AA").unwrap();
-        let problem = Problem::new(30, false, &self.valves, *start_position);
-        
-        let solver = Solver { problem: &problem };
+        Ok(d)
+    }
 
-        let soln= solver.solve();
-        match soln {
-            Some(soln) => {
-                Answer::Number(soln.flow_captured)
-            }
-            None => {
-                Answer::None
+    // The tunnels as a .dot graph (see to_dot), with options graph=tunnels
+    // or graph=valves for the compressed graph, plan=AGENTS,MINUTES to draw
+    // the best plan for that many agents in that many minutes, and
+    // start=NAME for somewhere other than AA.
+    fn export(&self, format: &str, options: &[(String, String)]) -> Result<Vec<u8>, String> {
+        if format != "dot" {
+            return Err(format!("can't draw the tunnels as '{}', only dot", format));
+        }
+        let mut compressed = false;
+        let mut plan = None;
+        let mut start = "AA";
+        for (key, value) in options {
+            match key.as_str() {
+                "graph" => compressed = match value.as_str() {
+                    "tunnels" => false,
+                    "valves" => true,
+                    _ => return Err(format!("graph should be tunnels or valves, not '{}'", value)),
+                },
+                "plan" => {
+                    let numbers: Vec<usize> = value.split(',')
+                        .map(|n| n.trim().parse::<usize>())
+                        .collect::<Result<_, _>>()
+                        .map_err(|_| format!("plan should be AGENTS,MINUTES, not '{}'", value))?;
+                    match numbers[..] {
+                        [agents, minutes] => plan = Some((agents, minutes)),
+                        _ => return Err(format!("plan should be AGENTS,MINUTES, not '{}'", value)),
+                    }
+                }
+                "start" => start = value,
+                _ => return Err(format!("unknown export option '{}'", key)),
             }
         }
-    }
 
-    fn part2(&self) -> Answer {
-        let start_position = self.valve_ids.get("AA").unwrap();
-        let problem = Problem::new(26, true, &self.valves, *start_position);
-        let solver = Solver { problem: &problem };
+        let (agents, minutes) = plan.unwrap_or((1, 30));
+        let problem = self.problem(agents, minutes, start).ok_or(format!("there is no valve {}", start))?;
+        let solution = plan.and_then(|(agents, minutes)| self.optimise(agents, minutes, start));
 
-        let soln= solver.solve();
-        match soln {
-            Some(soln) => {
-                Answer::Number(soln.flow_captured)
-            }
-            None => {
-                Answer::None
+        Ok(self.to_dot(&problem, compressed, solution.as_ref()).into_bytes())
+    }
+
+    fn set_variant(&mut self, variant: &str) -> Result<(), String> {
+        (self.method, self.bound) = match variant {
+            "branch_bound" => (Method::BranchBound, Bound::Tightest),
+            "dp" => {
+                let count = self.valves.values().filter(|v| v.flow_rate > 0).count();
+                if count > MAX_SUBSET_VALVES {
+                    return Err(format!("dp can't handle {} valves with a flow, only up to {}", count, MAX_SUBSET_VALVES));
+                }
+                (Method::Subsets, Bound::Tightest)
             }
-        }
+            "greedy_bound" => (Method::BranchBound, Bound::Greedy),
+            "distance_bound" => (Method::BranchBound, Bound::Distance),
+            _ => return Err(format!("no variant '{}'", variant)),
+        };
+
+        Ok(())
+    }
+
+    fn part1(&self) -> Answer {
+        self.answer(1, 30)
+    }
+
+    fn part2(&self) -> Answer {
+        // An elephant helps, but teaching it takes 4 minutes.
+        self.answer(2, 26)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testutil::random_cases;
     //use crate::day::Day;
 
     #[test]
     fn test_load() {
-        let d = Day16::load("examples/day16_example1.txt");
+        let d = Day16::load("examples/day16_example1.txt").unwrap();
         assert_eq!(d.valves.len(), 10);
     }
 
     #[test]
     fn test_create_problem() {
-        let d = Day16::load("examples/day16_example1.txt");
+        let d = Day16::load("examples/day16_example1.txt").unwrap();
         let start_position = d.valve_ids.get("AA").unwrap();
-        let problem = Problem::new(30, false, &d.valves, *start_position);
+        let mut problem = Problem::new(30, 1, &d.valves, *start_position);
+        problem.bound = Bound::Greedy;
         assert_eq!(problem.period, 30);
-        assert_eq!(problem.two_agents, false);
+        assert_eq!(problem.agents, 1);
         assert_eq!(problem.valves.len(), 10);
 
         let start = problem.get_start();
@@ -466,9 +918,10 @@ mod tests {
         let start_position = d.valve_ids.get("AA").unwrap();
         assert_eq!(*start_position, 0);
 
-        let problem2 = Problem::new(26, true, &d.valves, *start_position);
+        let mut problem2 = Problem::new(26, 2, &d.valves, *start_position);
+        problem2.bound = Bound::Greedy;
         assert_eq!(problem2.period, 26);
-        assert_eq!(problem2.two_agents, true);
+        assert_eq!(problem2.agents, 2);
         assert_eq!(problem2.valves.len(), 10);
 
         let start2 = problem2.get_start();
@@ -482,18 +935,10 @@ mod tests {
         assert_eq!(*start_position, 0);
     }
 
-    #[test]
-    fn test_create_solver() {
-        let d = Day16::load("examples/day16_example1.txt");
-        let start_position = d.valve_ids.get("AA").unwrap();
-        let problem = Problem::new(30, false, &d.valves, *start_position);
-        let _solver = Solver { problem: &problem };
-    }
-
 /* 
     #[test]
     fn test_get_start() {
-        let d = Day16::load("examples/day16_example1.txt");
+        let d = Day16::load("examples/day16_example1.txt").unwrap();
         let initial = d.get_start_p1();
         assert_eq!(initial.ttg, 30);
         assert_eq!(Some(&initial.position1), d.valve_ids.get("AA"));
@@ -505,7 +950,7 @@ mod tests {
 
     #[test]
     fn test_value_functions() {
-        let d = Day16::load("examples/day16_example1.txt");
+        let d = Day16::load("examples/day16_example1.txt").unwrap();
         let initial = d.get_start_p1();
         assert_eq!(initial.cost(), 0);
         assert_eq!(initial.completion_estimate(), 2227);
@@ -513,7 +958,7 @@ mod tests {
 
     #[test]
     fn test_next_states() {
-        let d = Day16::load("examples/day16_example1.txt");
+        let d = Day16::load("examples/day16_example1.txt").unwrap();
         let initial = d.get_start_p1();
         let nexts = initial.next_states();
 
@@ -532,7 +977,7 @@ mod tests {
     
     #[test]
     fn test_next_next_states() {
-        let d = Day16::load("examples/day16_example1.txt");
+        let d = Day16::load("examples/day16_example1.txt").unwrap();
         let initial = d.get_start_p1();
         let nexts = initial.next_states();
         let nn = nexts[0].next_states();
@@ -557,7 +1002,7 @@ mod tests {
 
     #[test]
     fn test_next_next_states2() {
-        let d = Day16::load("examples/day16_example1.txt");
+        let d = Day16::load("examples/day16_example1.txt").unwrap();
         let initial = d.get_start_p1();
         let nexts = initial.next_states();
         let nn = nexts[1].next_states();
@@ -576,40 +1021,269 @@ mod tests {
 
     #[test]
     fn test_search() {
-        let d = Day16::load("examples/day16_example1.txt");
+        let d = Day16::load("examples/day16_example1.txt").unwrap();
 
         let start_position = d.valve_ids.get("AA").unwrap();
-        let problem = Problem::new(30, false, &d.valves, *start_position);
-
-        let solver = Solver { problem: &problem };
-        let solution = solver.solve().unwrap();
+        let problem = Problem::new(30, 1, &d.valves, *start_position);
 
-        println!("Search found {:?}", solution.opens[0]);
-        println!("Flow Captured: {:?}", solution.flow_captured);
+        let mut solver = BranchAndBound::new(&problem);
+        let solution = solver.solve();
 
         assert_eq!(solution.flow_captured, 1651);
+        assert_eq!(solution.opens[0].len(), 6);
+        let stats = solver.stats();
+        assert!(stats.pruned > 0);
+        assert_eq!(stats.improvements.last().unwrap().score, 1651);
     }
 
     
     #[test]
     fn test_search2() {
-        let d = Day16::load("examples/day16_example1.txt");
+        let d = Day16::load("examples/day16_example1.txt").unwrap();
 
         let start_position = d.valve_ids.get("AA").unwrap();
-        let problem = Problem::new(26, true, &d.valves, *start_position);
+        let problem = Problem::new(26, 2, &d.valves, *start_position);
 
-        let solver = Solver { problem: &problem };
-        let solution = solver.solve().unwrap();
-
-        println!("Search found {:?}, {:?}", solution.opens[0], solution.opens[1]);
-        println!("Flow Captured: {:?}", solution.flow_captured);
+        let solution = BranchAndBound::new(&problem).solve();
 
         assert_eq!(solution.flow_captured, 1707);
     }
+
+    // Every complete solution's flow, the best of which branch and bound
+    // should find.
+    fn exhaustive(problem: &Problem) -> usize {
+        let mut best = 0;
+        let mut to_visit = vec![problem.get_start()];
+        while let Some(soln) = to_visit.pop() {
+            best = best.max(soln.flow_captured);
+            to_visit.extend(soln.get_next_steps(problem));
+        }
+        best
+    }
+
+    #[test]
+    fn test_optimise() {
+        let d = Day16::load("examples/day16_example1.txt").unwrap();
+        assert_eq!(d.optimise(1, 30, "AA").unwrap().flow_captured, 1651);
+        assert_eq!(d.optimise(2, 26, "AA").unwrap().flow_captured, 1707);
+        assert_eq!(d.optimise(0, 30, "AA").unwrap().flow_captured, 0);
+        assert_eq!(d.optimise(2, 0, "AA").unwrap().flow_captured, 0);
+        assert!(d.optimise(1, 30, "ZZ").is_none());
+
+        // What if two elephants helped, or we started elsewhere?
+        for (agents, minutes, start) in [(3, 12, "AA"), (1, 20, "JJ"), (2, 15, "EE")] {
+            let solution = d.optimise(agents, minutes, start).unwrap();
+            assert_eq!(solution.opens.len(), agents);
+            let problem = Problem::new(minutes, agents, &d.valves, d.valve_ids[start]);
+            assert_eq!(solution.flow_captured, exhaustive(&problem), "{} agents, {} minutes from {}", agents, minutes, start);
+        }
+    }
+
+    #[test]
+    fn test_subsets() {
+        let mut d = Day16::load("examples/day16_example1.txt").unwrap();
+        d.set_variant("dp").unwrap();
+        assert_eq!(d.method, Method::Subsets);
+        assert_eq!(d.part1().value(), &Answer::Number(1651));
+        assert_eq!(d.part2().value(), &Answer::Number(1707));
+        assert_eq!(d.optimise(0, 30, "AA").unwrap().flow_captured, 0);
+        let solution = d.optimise(3, 26, "AA").unwrap();
+        assert_eq!(solution.opens.len(), 3);
+
+        // Every valve with a flow is opened at most once, by one agent.
+        let opened: Vec<&usize> = solution.opens.iter().flatten().collect();
+        let unique: HashSet<&usize> = opened.iter().copied().collect();
+        assert_eq!(opened.len(), unique.len());
+        assert!(opened.iter().all(|id| d.valves[id].flow_rate > 0));
+
+        d.set_variant("branch_bound").unwrap();
+        assert_eq!(d.method, Method::BranchBound);
+        assert_eq!(d.optimise(3, 26, "AA").unwrap().flow_captured, solution.flow_captured);
+    }
+
+    #[test]
+    fn test_itinerary() {
+        let mut d = Day16::load("examples/day16_example1.txt").unwrap();
+        let part1 = d.part1();
+        let itinerary = part1.detail().unwrap();
+        assert!(itinerary.starts_with("== Minute 1 ==\nNo valves are open.\nYou move to valve DD.\n\n\
+                                       == Minute 2 ==\nNo valves are open.\nYou open valve DD.\n\n\
+                                       == Minute 3 ==\nValve DD is open, releasing 20 pressure (20 in all).\nYou move to valve CC.\n"));
+        assert!(itinerary.contains("== Minute 6 ==\nValves BB and DD are open, releasing 33 pressure (93 in all).\n"));
+        assert!(itinerary.contains("== Minute 30 ==\nValves BB, CC, DD, EE, HH, and JJ are open, releasing 81 pressure (1651 in all).\n\n\
+                                    Branch and bound with the tightest bound expanded "));
+        d.set_variant("dp").unwrap();
+        assert!(d.part1().detail().unwrap().ends_with("(1651 in all)."));
+
+        // Whoever finds the plan, it should release what it claims to.
+        for variant in ["branch_bound", "dp"] {
+            d.set_variant(variant).unwrap();
+            for (agents, minutes) in [(2, 26), (3, 10), (1, 5), (0, 3)] {
+                let problem = d.problem(agents, minutes, "AA").unwrap();
+                let solution = d.solve(&problem);
+                let itinerary = solution.itinerary(&problem);
+                assert_eq!(itinerary.matches("== Minute").count(), minutes);
+                let last = itinerary.rsplit("== Minute").next().unwrap();
+                let released = match last.split_once('(') {
+                    Some((_, rest)) => rest.split(' ').next().unwrap().parse().unwrap(),
+                    None => 0,
+                };
+                assert_eq!(solution.flow_captured, released, "{}: {} agents, {} minutes", variant, agents, minutes);
+                assert_eq!(itinerary.contains("The elephant"), agents >= 2 && minutes > 0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_variants() {
+        // Every variant in the registry is accepted, and nothing else.
+        let mut d = Day16::load("examples/day16_example1.txt").unwrap();
+        for variant in crate::registry::find(16).unwrap().variants {
+            d.set_variant(variant).unwrap();
+            assert_eq!(d.part1().value(), &Answer::Number(1651), "{}", variant);
+        }
+        d.set_variant("branch_bound").unwrap();
+        assert_eq!(d.set_variant("bogus"), Err("no variant 'bogus'".to_string()));
+        assert_eq!((d.method, d.bound), (Method::BranchBound, Bound::Tightest));
+    }
+
+    #[test]
+    fn test_many_valves() {
+        // A line of valves, all with a flow.
+        let network = |count: usize| -> Day16 {
+            let name = |i: usize| format!("A{}", (b'A' + i as u8) as char);
+            let text: String = (0..count).map(|i| {
+                let leads: Vec<String> = [i.wrapping_sub(1), i + 1].into_iter()
+                    .filter(|j| *j < count)
+                    .map(name)
+                    .collect();
+                format!("Valve {} has flow rate={}; tunnels lead to valves {}\n", name(i), 1 + i % 7, leads.join(", "))
+            }).collect();
+            Day16::from_str(&text).unwrap()
+        };
+
+        // Too many for dp to work through every set of them, but not for
+        // branch and bound.
+        let mut d = network(MAX_SUBSET_VALVES + 1);
+        assert_eq!(d.set_variant("dp"), Err("dp can't handle 17 valves with a flow, only up to 16".to_string()));
+        assert_eq!(d.method, Method::BranchBound);
+        assert!(d.optimise(2, 10, "AA").unwrap().flow_captured > 0);
+
+        assert!(network(MAX_SUBSET_VALVES).set_variant("dp").is_ok());
+    }
+
+    #[test]
+    fn test_bounds() {
+        let mut d = Day16::load("examples/day16_example1.txt").unwrap();
+        let mut expanded: HashMap<&str, usize> = HashMap::new();
+        for variant in ["greedy_bound", "distance_bound", "branch_bound"] {
+            d.set_variant(variant).unwrap();
+            let problem = d.problem(2, 26, "AA").unwrap();
+            let (solution, stats) = d.search(&problem);
+            assert_eq!(solution.flow_captured, 1707);
+            expanded.insert(variant, stats.unwrap().expanded);
+            assert!(problem.get_start().max_uncaptured >= 1707);
+        }
+
+        // Knowing how far away the valves are saves a lot of searching.
+        assert!(expanded["distance_bound"] * 10 < expanded["greedy_bound"]);
+        assert!(expanded["branch_bound"] <= expanded["distance_bound"]);
+
+        d.set_variant("distance_bound").unwrap();
+        assert_eq!((d.method, d.bound), (Method::BranchBound, Bound::Distance));
+        d.set_variant("dp").unwrap();
+        assert_eq!(d.search(&d.problem(2, 26, "AA").unwrap()).1, None);
+    }
+
+    #[test]
+    fn test_export() {
+        let d = Day16::load("examples/day16_example1.txt").unwrap();
+        let option = |key: &str, value: &str| (key.to_string(), value.to_string());
+        let dot = |options: &[(String, String)]| String::from_utf8(d.export("dot", options).unwrap()).unwrap();
+
+        let tunnels = dot(&[]);
+        assert!(tunnels.starts_with("graph tunnels {\n    node [shape=circle];\n    AA [label=\"AA\", style=bold];\n"));
+        assert!(tunnels.contains("    BB [label=\"BB\\n13\", shape=doublecircle];\n"));
+        assert_eq!(tunnels.matches(" -- ").count(), 10);
+        assert!(tunnels.contains("    AA -- DD;\n"));
+        assert!(tunnels.ends_with("}\n"));
+
+        // The compressed graph joins the start and the 6 valves worth opening.
+        let valves = dot(&[option("graph", "valves")]);
+        assert!(valves.starts_with("graph valves {"));
+        assert!(!valves.contains("FF"));
+        assert_eq!(valves.matches(" -- ").count(), 7 * 6 / 2);
+        assert!(valves.contains("    BB -- JJ [label=\"3\"];\n"));
+
+        // You go to DD first, then to BB by way of CC, and end up using
+        // every tunnel.
+        let plan = dot(&[option("plan", "1,30")]);
+        assert!(plan.contains("    DD [label=\"DD\\n20\\nopened 2\", shape=doublecircle, color=\"red\"];\n"));
+        assert!(plan.contains("    AA -- DD [color=\"red\", penwidth=3];\n"));
+        assert!(plan.contains("    BB -- CC [color=\"red\", penwidth=3];\n"));
+        assert_eq!(plan.matches("penwidth=3").count(), 10);
+        assert!(plan.contains("    label=\"1 agents, 30 minutes from AA: 1651 released\";\n"));
+        let plan = dot(&[option("plan", "2,26"), option("graph", "valves")]);
+        // One of you goes to DD and the other to JJ.
+        let colour = |edge: &str| plan.split(edge).nth(1).unwrap().split('"').nth(3).unwrap().to_string();
+        let (dd, jj) = (colour("AA -- DD"), colour("AA -- JJ"));
+        assert!(dd != jj && ROUTE_COLOURS[..2].contains(&dd.as_str()) && ROUTE_COLOURS[..2].contains(&jj.as_str()));
+        assert_eq!(plan.matches("penwidth=3").count(), 6);
+        // What if two elephants helped?
+        let plan = dot(&[option("plan", "3,26")]);
+        assert!(plan.contains("    label=\"3 agents, 26 minutes from AA: "));
+        assert!(!tunnels.contains("    label="));
+        assert!(dot(&[option("start", "JJ")]).contains("    JJ [label=\"JJ\\n21\", shape=doublecircle, style=bold];\n"));
+
+        assert!(d.export("png", &[]).is_err());
+        assert!(d.export("dot", &[option("graph", "maze")]).is_err());
+        assert!(d.export("dot", &[option("plan", "2")]).is_err());
+        assert!(d.export("dot", &[option("start", "ZZ")]).is_err());
+        assert!(d.export("dot", &[option("colour", "red")]).is_err());
+    }
+
+    #[test]
+    fn test_random_networks() {
+        // Both methods should agree on any connected network of tunnels.
+        random_cases(16, 20, |next| {
+            let count = 4 + next(6);
+            let names: Vec<String> = (0..count).map(|i| format!("A{}", (b'A' + i as u8) as char)).collect();
+            let mut tunnels: Vec<HashSet<usize>> = vec![HashSet::new(); count];
+            for i in 1..count {
+                // Joined to an earlier valve so that everything can be reached.
+                let j = next(i);
+                tunnels[i].insert(j);
+                tunnels[j].insert(i);
+            }
+            for _ in 0..next(4) {
+                let (i, j) = (next(count), next(count));
+                if i != j {
+                    tunnels[i].insert(j);
+                    tunnels[j].insert(i);
+                }
+            }
+            let text: String = (0..count).map(|i| {
+                let flow_rate = if next(3) == 0 { 0 } else { next(25) };
+                let mut leads: Vec<&str> = tunnels[i].iter().map(|j| names[*j].as_str()).collect();
+                leads.sort();
+                format!("Valve {} has flow rate={}; tunnels lead to valves {}\n", names[i], flow_rate, leads.join(", "))
+            }).collect();
+
+            let mut d = Day16::from_str(&text).unwrap();
+            let (agents, minutes) = (1 + next(3), 5 + next(12));
+            let start = names[next(count)].as_str();
+            let by_subsets = { d.set_variant("dp").unwrap(); d.optimise(agents, minutes, start).unwrap() };
+            for variant in ["branch_bound", "greedy_bound", "distance_bound"] {
+                d.set_variant(variant).unwrap();
+                let by_bound = d.optimise(agents, minutes, start).unwrap();
+                assert_eq!(by_subsets.flow_captured, by_bound.flow_captured, "{}{}: {} agents, {} minutes from {}", text, variant, agents, minutes, start);
+            }
+        });
+    }
 /*
     #[test]
     fn test_search2() {
-        let d = Day16::load("examples/day16_example1.txt");
+        let d = Day16::load("examples/day16_example1.txt").unwrap();
         let initial = d.get_start_p2();
 
         let mut searcher: AStarSearch<State, StateKey> = AStarSearch::new(false, false);
@@ -625,7 +1299,7 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        let d = Day16::load("examples/day16_example1.txt");
+        let d = Day16::load("examples/day16_example1.txt").unwrap();
 
         assert_eq!(d.part1(), Answer::Number(1651));
     }
@@ -633,7 +1307,7 @@ mod tests {
 
     #[test]
     fn test_part2() {
-        let d = Day16::load("examples/day16_example1.txt");
+        let d = Day16::load("examples/day16_example1.txt").unwrap();
 
         assert_eq!(d.part2(), Answer::Number(1707));
     }
@@ -1,6 +1,6 @@
 use crate::day::{Day, Answer};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use crate::error::AocError;
+use crate::input::Input;
 
 #[derive(Debug, PartialEq)]
 enum Rock {
@@ -331,20 +331,23 @@ pub struct Day17 {
     winds: String,
 }
 
-impl Day17 {
-    pub fn load(filename: &str) -> Day17 {
-        let mut winds: String = String::new();
-
-        let file = File::open(filename).unwrap();
-        let mut reader = BufReader::new(file);
+impl Day for Day17 {
+    fn parse(input: &Input) -> Result<Day17, AocError> {
+        let line = input.first_line()?;
+        let winds = line.text.trim();
 
-        reader.read_line(&mut winds).unwrap();
+        if winds.is_empty() {
+            return Err(input.invalid("no jet pattern"));
+        }
+        let leading = line.text.len() - line.text.trim_start().len();
+        if let Some(col) = winds.find(|c| c != '<' && c != '>') {
+            let c = winds[col..].chars().next().unwrap();
+            return Err(line.error(leading + col + 1, &format!("unexpected character '{}' in jet pattern", c)));
+        }
 
-        Day17 { winds: winds.trim().to_string() }
+        Ok(Day17 { winds: winds.to_string() })
     }
-}
 
-impl Day for Day17 {
     fn part1(&self) -> Answer {
         let mut sim = Sim::new(&self.winds);
 
@@ -369,7 +372,7 @@ mod tests {
 
     #[test]
     fn test_load() {
-        let d = Day17::load("examples/day17_example1.txt");
+        let d = Day17::load("examples/day17_example1.txt").unwrap();
         assert_eq!(d.winds.len(), 40);
     }
 
@@ -404,7 +407,7 @@ mod tests {
 
     #[test]
     fn test_drop_rock1() {
-        let d = Day17::load("examples/day17_example1.txt");
+        let d = Day17::load("examples/day17_example1.txt").unwrap();
         let mut sim = Sim::new(&d.winds);
 
         sim.drop_rock();
@@ -415,7 +418,7 @@ mod tests {
 
     #[test]
     fn test_drop_rock2() {
-        let d = Day17::load("examples/day17_example1.txt");
+        let d = Day17::load("examples/day17_example1.txt").unwrap();
         let mut sim = Sim::new(&d.winds);
 
         sim.drop_rock();
@@ -427,7 +430,7 @@ mod tests {
 
     #[test]
     fn test_drop_rocks_10() {
-        let d = Day17::load("examples/day17_example1.txt");
+        let d = Day17::load("examples/day17_example1.txt").unwrap();
         let mut sim = Sim::new(&d.winds);
 
         for _count in 0..10 {
@@ -439,7 +442,7 @@ mod tests {
 
     #[test]
     fn test_drop_rocks_2022() {
-        let d = Day17::load("examples/day17_example1.txt");
+        let d = Day17::load("examples/day17_example1.txt").unwrap();
         let mut sim = Sim::new(&d.winds);
 
         for _count in 0..2022 {
@@ -451,7 +454,7 @@ mod tests {
 
     #[test]
     fn test_drop_rock_1m() {
-        let d = Day17::load("examples/day17_example1.txt");
+        let d = Day17::load("examples/day17_example1.txt").unwrap();
         let mut sim = Sim::new(&d.winds);
 
         for _count in 0..1000000 {
@@ -463,7 +466,7 @@ mod tests {
     
     #[test]
     fn test_drop_rocks_1m() {
-        let d = Day17::load("examples/day17_example1.txt");
+        let d = Day17::load("examples/day17_example1.txt").unwrap();
         let mut sim = Sim::new(&d.winds);
 
         sim.drop_rocks(1000000);
@@ -473,7 +476,7 @@ mod tests {
 
     #[test]
     fn test_drop_rocks_1b() {
-        let d = Day17::load("examples/day17_example1.txt");
+        let d = Day17::load("examples/day17_example1.txt").unwrap();
         let mut sim = Sim::new(&d.winds);
 
         sim.drop_rocks(1_000_000_000);
@@ -484,7 +487,7 @@ mod tests {
 
     #[test]
     fn test_drop_rocks_1t() {
-        let d = Day17::load("examples/day17_example1.txt");
+        let d = Day17::load("examples/day17_example1.txt").unwrap();
         let mut sim = Sim::new(&d.winds);
 
         sim.drop_rocks(1000000000000_usize);
@@ -494,7 +497,7 @@ mod tests {
 
     #[test]
     fn test_example_len() {
-        let d = Day17::load("examples/day17_example1.txt");
+        let d = Day17::load("examples/day17_example1.txt").unwrap();
 
         assert_eq!(d.winds.len(), 40);
     }
@@ -503,7 +506,7 @@ mod tests {
 
     #[test]
     fn test_periodicity() {
-        let d = Day17::load("data_aoc2022/day17_input.txt");
+        let d = Day17::load("data_aoc2022/day17_input.txt").unwrap();
         let period = d.winds.len() * 5;
         let mut sim = Sim::new(&d.winds);
 
@@ -520,4 +523,16 @@ mod tests {
         assert!(true);
     }
     */
+
+    #[test]
+    fn test_from_reader() {
+        let reader = std::io::Cursor::new(">>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>\n");
+        let d = Day17::from_reader(reader).unwrap();
+        assert_eq!(d.part1(), Answer::Number(3068));
+
+        match Day17::from_str("<<x>") {
+            Err(AocError::Parse { column, .. }) => assert_eq!(column, 3),
+            _ => panic!("expected a parse error"),
+        }
+    }
 }
@@ -1,6 +1,6 @@
 use crate::day::{Day, Answer};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use crate::error::AocError;
+use crate::input::Input;
 use lazy_static::lazy_static;
 use regex::Regex;
 
@@ -21,42 +21,6 @@ pub struct Day2 {
 }
 
 impl Day2 {
-    pub fn load(filename: &str) -> Day2 {
-        let mut plays: Vec<(Rps, Xyz)> = Vec::new();
-        lazy_static! {
-            static ref LINE_RE: Regex =
-                Regex::new("([ABC]) ([XYZ])").unwrap();
-        }
-
-        let file = File::open(filename).unwrap();
-        let reader = BufReader::new(file);
-
-        for line in reader.lines() {
-            let l = &line.unwrap();
-            let caps = LINE_RE.captures(&l);
-            match caps {
-                Some(caps) => {
-                    let other = match &caps[1] {
-                        "A" => { Rps::ROCK }
-                        "B" => { Rps::PAPER }
-                        "C" => { Rps::SCISSORS }
-                        _ => { panic!("Invalid letter for opponent's play."); }
-                    };
-                    let me = match &caps[2] {
-                        "X" => {Xyz::X}
-                        "Y" => {Xyz::Y}
-                        "Z" => {Xyz::Z}
-                        _ => { panic!("Invalid letter for my play."); }
-                    };
-                    plays.push( (other, me) );
-                }
-                _ => ()
-            }
-        }
-
-        Day2 { plays }
-    }
-
     fn score_match(&self) -> usize {
         let mut score = 0;
         for (opponent, me) in self.plays.iter() {
@@ -138,6 +102,37 @@ impl Day2 {
 }
 
 impl Day for Day2 {
+    fn parse(input: &Input) -> Result<Day2, AocError> {
+        let mut plays: Vec<(Rps, Xyz)> = Vec::new();
+        lazy_static! {
+            static ref LINE_RE: Regex =
+                Regex::new("([ABC]) ([XYZ])").unwrap();
+        }
+
+        for line in input.lines() {
+            match LINE_RE.captures(line.text) {
+                Some(caps) => {
+                    let other = match &caps[1] {
+                        "A" => { Rps::ROCK }
+                        "B" => { Rps::PAPER }
+                        "C" => { Rps::SCISSORS }
+                        _ => { panic!("Invalid letter for opponent's play."); }
+                    };
+                    let me = match &caps[2] {
+                        "X" => {Xyz::X}
+                        "Y" => {Xyz::Y}
+                        "Z" => {Xyz::Z}
+                        _ => { panic!("Invalid letter for my play."); }
+                    };
+                    plays.push( (other, me) );
+                }
+                None => line.unmatched()?,
+            }
+        }
+
+        Ok(Day2 { plays })
+    }
+
     fn part1(&self) -> Answer {
         Answer::Number(self.score_match())
     }
@@ -154,19 +149,19 @@ mod tests {
 
     #[test]
     fn test_load() {
-        let d = Day2::load("examples/day2_example1.txt");
+        let d = Day2::load("examples/day2_example1.txt").unwrap();
         assert_eq!(d.plays.len(), 3);
     }
 
     #[test]
     fn test_score() {
-        let d = Day2::load("examples/day2_example1.txt");
+        let d = Day2::load("examples/day2_example1.txt").unwrap();
         assert_eq!(d.score_match(), 15);
     }
 
     #[test]
     fn test_score2() {
-        let d = Day2::load("examples/day2_example1.txt");
+        let d = Day2::load("examples/day2_example1.txt").unwrap();
         assert_eq!(d.score_match2(), 12);
     }
 }
@@ -1,6 +1,6 @@
 use crate::day::{Day, Answer};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use crate::error::AocError;
+use crate::input::Input;
 use lazy_static::lazy_static;
 use regex::Regex;
 
@@ -9,31 +9,6 @@ pub struct Day3 {
 }
 
 impl Day3 {
-    pub fn load(filename: &str) -> Day3 {
-        let mut rucksacks: Vec<String> = Vec::new();
-        lazy_static! {
-            static ref LINE_RE: Regex =
-                Regex::new("([a-zA-Z]+)").unwrap();
-        }
-
-        let file = File::open(filename).unwrap();
-        let reader = BufReader::new(file);
-
-        for line in reader.lines() {
-            let l = &line.unwrap();
-            let caps = LINE_RE.captures(&l);
-            match caps {
-                Some(caps) => {
-                    let contents = caps[1].to_string();
-                    rucksacks.push(contents);
-                }
-                None => {}
-            }
-        }
-
-        Day3 { rucksacks }
-    }
-
     fn priority(c: char) -> usize {
         let p = if (c >= 'a') && (c <= 'z') {
             (c as u32) - ('a' as u32) + 1
@@ -102,6 +77,26 @@ impl Day3 {
 }
 
 impl Day for Day3 {
+    fn parse(input: &Input) -> Result<Day3, AocError> {
+        let mut rucksacks: Vec<String> = Vec::new();
+        lazy_static! {
+            static ref LINE_RE: Regex =
+                Regex::new("([a-zA-Z]+)").unwrap();
+        }
+
+        for line in input.lines() {
+            match LINE_RE.captures(line.text) {
+                Some(caps) => {
+                    let contents = caps[1].to_string();
+                    rucksacks.push(contents);
+                }
+                None => line.unmatched()?,
+            }
+        }
+
+        Ok(Day3 { rucksacks })
+    }
+
     fn part1(&self) -> Answer {
         Answer::Number(self.priority_sum())
     }
@@ -118,19 +113,19 @@ mod tests {
 
     #[test]
     fn test_load() {
-        let d = Day3::load("examples/day3_example1.txt");
+        let d = Day3::load("examples/day3_example1.txt").unwrap();
         assert_eq!(d.rucksacks.len(), 6);
     }
 
     #[test]
     fn test_priority_sum() {
-        let d = Day3::load("examples/day3_example1.txt");
+        let d = Day3::load("examples/day3_example1.txt").unwrap();
         assert_eq!(d.priority_sum(), 157);
     }
 
     #[test]
     fn test_badge_sum() {
-        let d = Day3::load("examples/day3_example1.txt");
+        let d = Day3::load("examples/day3_example1.txt").unwrap();
         assert_eq!(d.badge_sum(), 70);
     }
 }
@@ -1,6 +1,7 @@
 use crate::day::{Day, Answer};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use crate::error::AocError;
+use crate::input::Input;
+use crate::intervals::IntervalSet;
 use lazy_static::lazy_static;
 use regex::Regex;
 
@@ -15,69 +16,67 @@ pub struct Day4 {
     assignment_pairs: Vec<AssignmentPair>,
 }
 
+impl AssignmentPair {
+    fn sections(&self) -> (IntervalSet, IntervalSet) {
+        (IntervalSet::single(self.low1 as isize, self.high1 as isize),
+         IntervalSet::single(self.low2 as isize, self.high2 as isize))
+    }
+}
+
 impl Day4 {
-    pub fn load(filename: &str) -> Day4 {
+    // return the number of pairs where one is fully contained in the other.
+    fn fully_contained(&self) -> usize {
+        self.assignment_pairs.iter()
+            .filter(|ap| {
+                let (first, second) = ap.sections();
+                first.contains_set(&second) || second.contains_set(&first)
+            })
+            .count()
+    }
+
+    // return the number of pairs that overlap at all.
+    fn overlap(&self) -> usize {
+        self.assignment_pairs.iter()
+            .filter(|ap| {
+                let (first, second) = ap.sections();
+                !first.intersection(&second).is_empty()
+            })
+            .count()
+    }
+}
+
+impl Day for Day4 {
+    fn parse(input: &Input) -> Result<Day4, AocError> {
         let mut assignment_pairs: Vec<AssignmentPair> = Vec::new();
         lazy_static! {
             static ref LINE_RE: Regex =
                 Regex::new("([0-9]+)-([0-9]+),([0-9]+)-([0-9]+)").unwrap();
         }
 
-        let file = File::open(filename).unwrap();
-        let reader = BufReader::new(file);
-
-        for line in reader.lines() {
-            let l = &line.unwrap();
-            let caps = LINE_RE.captures(&l);
-            match caps {
+        for line in input.lines() {
+            match LINE_RE.captures(line.text) {
                 Some(caps) => {
-                    let low1 = caps[1].parse::<usize>().unwrap();
-                    let high1 = caps[2].parse::<usize>().unwrap();
-                    let low2 = caps[3].parse::<usize>().unwrap();
-                    let high2 = caps[4].parse::<usize>().unwrap();
+                    let low1 = line.field(&caps, 1)?;
+                    let high1 = line.field(&caps, 2)?;
+                    let low2 = line.field(&caps, 3)?;
+                    let high2 = line.field(&caps, 4)?;
+                    // A backwards range would be an empty set of sections.
+                    for (low, high, n) in [(low1, high1, 1), (low2, high2, 3)] {
+                        if low > high {
+                            let column = caps.get(n).unwrap().start() + 1;
+                            return Err(line.error(column, &format!("range {}-{} runs backwards", low, high)));
+                        }
+                    }
 
                     assignment_pairs.push(AssignmentPair {low1, high1, low2, high2});
                 }
-                None => {}
+                None => line.unmatched()?,
             }
         }
 
-        Day4 { assignment_pairs }
-    }
-
-    // return the number of pairs where one is fully contained in the other.
-    fn fully_contained(&self) -> usize {
-        let mut total = 0;
-
-        for ap in &self.assignment_pairs {
-            if ((ap.low1 <= ap.low2) && (ap.high1 >= ap.high2)) ||  // first fully contains second
-               ((ap.low2 <= ap.low1) && (ap.high2 >= ap.high1)) {   // second fully contains first
-                   total += 1;
-               }    
-        }
-
-        total
+        Ok(Day4 { assignment_pairs })
     }
 
-    // return the number of pairs where one is fully contained in the other.
-    fn overlap(&self) -> usize {
-        let mut total = 0;
-
-        for ap in &self.assignment_pairs {
-            if ((ap.low1 <= ap.low2) && (ap.high1 >= ap.low2)) ||  // first fully contains second
-               ((ap.low1 <= ap.high2) && (ap.high1 >= ap.high2)) ||
-               ((ap.low1 >= ap.low2) && (ap.high1 <= ap.high2)) ||
-               ((ap.low2 >= ap.low1) && (ap.high2 <= ap.high1)) {
-                    total += 1;
-            }    
-        }
-
-        total
-    }
-
-}
-
-impl Day for Day4 {
     fn part1(&self) -> Answer {
         Answer::Number(self.fully_contained())
     }
@@ -90,23 +89,72 @@ impl Day for Day4 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testutil::random_cases;
     //use crate::day::Day;
 
     #[test]
     fn test_load() {
-        let d = Day4::load("examples/day4_example1.txt");
+        let d = Day4::load("examples/day4_example1.txt").unwrap();
         assert_eq!(d.assignment_pairs.len(), 6);
     }
 
     #[test]
     fn test_fully_contained() {
-        let d = Day4::load("examples/day4_example1.txt");
+        let d = Day4::load("examples/day4_example1.txt").unwrap();
         assert_eq!(d.fully_contained(), 2);
     }
 
     #[test]
     fn test_overlap() {
-        let d = Day4::load("examples/day4_example1.txt");
+        let d = Day4::load("examples/day4_example1.txt").unwrap();
         assert_eq!(d.overlap(), 4);
     }
+
+    #[test]
+    fn test_strict() {
+        // Unrecognized lines are skipped unless parsing strictly.
+        let input = Input::open("examples/day4_bad1.txt").unwrap();
+        let d = Day4::parse(&input).unwrap();
+        assert_eq!(d.assignment_pairs.len(), 3);
+
+        let input = input.strict(true);
+        match Day4::parse(&input) {
+            Err(AocError::Parse { line, column, .. }) => assert_eq!((line, column), (3, 1)),
+            _ => panic!("expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn test_backwards_range() {
+        // Reversed ranges are errors rather than empty assignments.
+        match Day4::from_str("2-4,6-8\n5-3,1-9\n") {
+            Err(AocError::Parse { line, column, .. }) => assert_eq!((line, column), (2, 1)),
+            _ => panic!("expected a parse error"),
+        }
+        match Day4::from_str("2-4,9-1\n") {
+            Err(AocError::Parse { line, column, .. }) => assert_eq!((line, column), (1, 5)),
+            _ => panic!("expected a parse error"),
+        }
+        assert_eq!(Day4::from_str("3-3,3-3\n").unwrap().overlap(), 1);
+    }
+
+    #[test]
+    fn test_random_pairs() {
+        // Checks each pair on its own against a brute force over the sections.
+        random_cases(4, 500, |next| {
+            let (a, b, c, d) = (1 + next(9), 1 + next(9), 1 + next(9), 1 + next(9));
+            let (low1, high1) = (a.min(b), a.max(b));
+            let (low2, high2) = (c.min(d), c.max(d));
+            let text = format!("{}-{},{}-{}", low1, high1, low2, high2);
+            let day = Day4::from_str(&text).unwrap();
+
+            let first: Vec<usize> = (low1..=high1).collect();
+            let second: Vec<usize> = (low2..=high2).collect();
+            let contained = first.iter().all(|s| second.contains(s)) || second.iter().all(|s| first.contains(s));
+            let overlaps = first.iter().any(|s| second.contains(s));
+
+            assert_eq!(day.fully_contained(), contained as usize, "{}", text);
+            assert_eq!(day.overlap(), overlaps as usize, "{}", text);
+        });
+    }
 }
@@ -1,6 +1,6 @@
 use crate::day::{Day, Answer};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use crate::error::AocError;
+use crate::input::Input;
 use lazy_static::lazy_static;
 use regex::Regex;
 
@@ -16,79 +16,6 @@ pub struct Day5 {
 }
 
 impl Day5 {
-    pub fn load(filename: &str) -> Day5 {
-
-        lazy_static! {
-            static ref CRATE_RE: Regex =
-                Regex::new("\\[[A-Z]\\]").unwrap();
-            static ref STACK_RE: Regex =
-                Regex::new(" 1   ").unwrap();
-            static ref MOVE_RE: Regex =
-                Regex::new("move ([0-9]+) from ([0-9]+) to ([0-9]+)").unwrap();
-        }
-
-        let mut crate_lines: Vec<String> = Vec::new();
-        let mut moves: Vec<Move> = Vec::new();
-        let mut stacks: Vec<Vec<char>> = Vec::new();
-
-        let file = File::open(filename).unwrap();
-        let reader = BufReader::new(file);
-
-        for line in reader.lines() {
-            let l = &line.unwrap();
-
-            // Process crate line by storing it for now.
-            let caps = CRATE_RE.captures(&l);
-            match caps {
-                Some(_caps) => {
-                    crate_lines.push(l.to_string());
-                }
-                None => {}
-            }
-
-            // Process stacks line, when it is seen process those crates.
-            let caps = STACK_RE.captures(&l);
-            match caps {
-                Some(_caps) => {
-                    // Determine number of stacks from line length.
-                    let num_stacks = (l.len()+1)/4;
-
-                    // Create the stacks
-                    for _ in 0..num_stacks {
-                        stacks.push(Vec::new());
-                    }
-
-                    // Process each line of crates
-                    while crate_lines.len() > 0 {
-                        let crate_line = crate_lines.pop().unwrap();
-                        for stack_no in 0..num_stacks {
-                            let crate_id = crate_line.chars().nth(stack_no*4+1).unwrap();
-                            if crate_id != ' ' {
-                                stacks[stack_no].push(crate_id);
-                            }
-                        }
-                    }
-                }
-                None => ()
-            }
-
-            // Process move line
-            let caps = MOVE_RE.captures(&l);
-            match caps {
-                Some(cap) => {
-                    let count = cap[1].parse::<usize>().unwrap();
-                    let from = cap[2].parse::<usize>().unwrap();
-                    let to = cap[3].parse::<usize>().unwrap();
-
-                    moves.push(Move {count, from, to});
-                }
-                None => ()
-            }
-        }
-
-        Day5 { stacks, moves }
-    }
-
     fn process_moves(&self, part1: bool) -> String {
         // Create mutable stacks to work with
         let mut work_stacks: Vec<Vec<char>> = Vec::new();
@@ -126,10 +53,12 @@ impl Day5 {
             }
         }
 
-        // Construct a string from the tops of the stacks
+        // Construct a string from the tops of the stacks, skipping empty ones
         let mut s = String::new();
         for work_stack in work_stacks {
-            s.push(*work_stack.last().unwrap());
+            if let Some(c) = work_stack.last() {
+                s.push(*c);
+            }
         }
 
         s
@@ -137,6 +66,83 @@ impl Day5 {
 }
 
 impl Day for Day5 {
+    fn parse(input: &Input) -> Result<Day5, AocError> {
+
+        lazy_static! {
+            static ref CRATE_RE: Regex =
+                Regex::new("\\[[A-Z]\\]").unwrap();
+            static ref STACK_RE: Regex =
+                Regex::new("^ *1( +[0-9]+)* *$").unwrap();
+            static ref MOVE_RE: Regex =
+                Regex::new("move ([0-9]+) from ([0-9]+) to ([0-9]+)").unwrap();
+        }
+
+        let mut crate_lines: Vec<String> = Vec::new();
+        let mut moves: Vec<Move> = Vec::new();
+        let mut stacks: Vec<Vec<char>> = Vec::new();
+
+        // Stack heights as the moves are applied, to catch moves that can't be made.
+        let mut heights: Vec<usize> = Vec::new();
+
+        for line in input.lines() {
+            let l = line.text;
+
+            if CRATE_RE.is_match(l) {
+                // Process crate line by storing it for now.
+                crate_lines.push(l.to_string());
+            }
+            else if STACK_RE.is_match(l) {
+                // Process stacks line, when it is seen process those crates.
+                // Count the labels, as trailing spaces may have been trimmed.
+                let num_stacks = l.split_whitespace().count();
+
+                // Create the stacks
+                for _ in 0..num_stacks {
+                    stacks.push(Vec::new());
+                }
+
+                // Process each line of crates
+                while let Some(crate_line) = crate_lines.pop() {
+                    for (stack_no, stack) in stacks.iter_mut().enumerate() {
+                        // Trailing spaces may have been trimmed from the line.
+                        let crate_id = crate_line.chars().nth(stack_no*4+1).unwrap_or(' ');
+                        if crate_id != ' ' {
+                            stack.push(crate_id);
+                        }
+                    }
+                }
+
+                heights = stacks.iter().map(|s| s.len()).collect();
+            }
+            else if let Some(caps) = MOVE_RE.captures(l) {
+                // Process move line
+                let count: usize = line.field(&caps, 1)?;
+                let from: usize = line.field(&caps, 2)?;
+                let to: usize = line.field(&caps, 3)?;
+
+                for n in [2, 3] {
+                    let stack_no: usize = line.field(&caps, n)?;
+                    if stack_no < 1 || stack_no > stacks.len() {
+                        let column = caps.get(n).unwrap().start() + 1;
+                        return Err(line.error(column, &format!("there is no stack {}", stack_no)));
+                    }
+                }
+                if heights[from-1] < count {
+                    return Err(line.error(1, &format!("stack {} only holds {} crates", from, heights[from-1])));
+                }
+                heights[from-1] -= count;
+                heights[to-1] += count;
+
+                moves.push(Move {count, from, to});
+            }
+            else {
+                line.unmatched()?;
+            }
+        }
+
+        Ok(Day5 { stacks, moves })
+    }
+
     fn part1(&self) -> Answer {
         // Ok(1)
         Answer::Message(self.process_moves(true))
@@ -155,7 +161,7 @@ mod tests {
 
     #[test]
     fn test_load() {
-        let d = Day5::load("examples/day5_example1.txt");
+        let d = Day5::load("examples/day5_example1.txt").unwrap();
         assert_eq!(d.stacks.len(), 3);
         assert_eq!(d.stacks[0].len(), 2);
         assert_eq!(d.stacks[1].len(), 3);
@@ -165,15 +171,38 @@ mod tests {
 
     #[test]
     fn test_process_moves() {
-        let d = Day5::load("examples/day5_example1.txt");
+        let d = Day5::load("examples/day5_example1.txt").unwrap();
         let s = d.process_moves(true);
         assert_eq!(s, "CMZ");
     }
 
     #[test]
     fn test_process_moves2() {
-        let d = Day5::load("examples/day5_example1.txt");
+        let d = Day5::load("examples/day5_example1.txt").unwrap();
         let s = d.process_moves(false);
         assert_eq!(s, "MCD");
     }
+
+    #[test]
+    fn test_empty_stack() {
+        // The only crate moves off stack 1, whose label line lost its trailing space.
+        for text in ["[A]    \n 1   2 \n\nmove 1 from 1 to 2\n", "[A]\n 1   2\n\nmove 1 from 1 to 2\n"] {
+            let d = Day5::from_str(text).unwrap();
+            assert_eq!(d.stacks.len(), 2);
+            assert_eq!(d.process_moves(true), "A");
+            assert_eq!(d.process_moves(false), "A");
+        }
+
+        let d = Day5::from_str("[A]\n 1\n\nmove 1 from 1 to 1\n").unwrap();
+        assert_eq!(d.process_moves(true), "A");
+        assert!(Day5::from_str("[A]\n 1   2\n\nmove 2 from 1 to 2\n").is_err());
+    }
+
+    #[test]
+    fn test_bad_move() {
+        match Day5::load("examples/day5_bad1.txt") {
+            Err(AocError::Parse { line, column, .. }) => assert_eq!((line, column), (7, 18)),
+            _ => panic!("expected a parse error"),
+        }
+    }
 }
@@ -1,6 +1,6 @@
 use crate::day::{Day, Answer};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use crate::error::AocError;
+use crate::input::Input;
 use std::collections::VecDeque;
 
 pub struct Day6 {
@@ -8,24 +8,6 @@ pub struct Day6 {
 }
 
 impl Day6 {
-    pub fn load(filename: &str) -> Day6 {
-        let mut seq: Vec<char> = Vec::new();
-
-        let file = File::open(filename).unwrap();
-        let mut reader = BufReader::new(file);
-
-        let mut s: String = String::new();
-        reader.read_line(&mut s).unwrap();
-
-        let trimmed = s.trim_end();
-
-        for c in trimmed.chars() {
-            seq.push(c);
-        }
-
-        Day6 { seq }
-    }
-
     fn find_no_repeat(&self, len:usize) -> usize {
         let mut marker: VecDeque<char> = VecDeque::new();
 
@@ -63,6 +45,18 @@ impl Day6 {
 }
 
 impl Day for Day6 {
+    fn parse(input: &Input) -> Result<Day6, AocError> {
+        let mut seq: Vec<char> = Vec::new();
+
+        let trimmed = input.first_line()?.text.trim_end();
+
+        for c in trimmed.chars() {
+            seq.push(c);
+        }
+
+        Ok(Day6 { seq })
+    }
+
     fn part1(&self) -> Answer {
         Answer::Number(self.find_no_repeat(4))
     }
@@ -79,7 +73,7 @@ mod tests {
 
     #[test]
     fn test_load() {
-        let d = Day6::load("examples/day6_example1.txt");
+        let d = Day6::load("examples/day6_example1.txt").unwrap();
         assert_eq!(d.seq.len(), 30);
     }
 
@@ -99,9 +93,17 @@ mod tests {
         );
 
         for (filename, len, expected) in cases {
-            let d = Day6::load(filename);
+            let d = Day6::load(filename).unwrap();
             let marker = d.find_no_repeat(len);
             assert_eq!(marker, expected)
         }
     }
+
+    #[test]
+    fn test_from_str() {
+        let d = Day6::from_str("bvwbjplbgvbhsrlpgdmjqwftvncz").unwrap();
+        assert_eq!(d.part1(), Answer::Number(5));
+        assert_eq!(d.part2(), Answer::Number(23));
+        assert!(Day6::from_str("").is_err());
+    }
 }
@@ -1,117 +1,134 @@
 use crate::day::{Day, Answer};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use crate::error::AocError;
+use crate::input::Input;
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::collections::HashMap;
 
+// One line of the terminal session.
+enum Entry {
+    Ls,
+    CdRoot,
+    CdUp,
+    Cd(String),
+    Dir,
+    File(usize),
+}
+
 pub struct Day7 {
-    session: Vec<String>,
+    session: Vec<Entry>,
 }
 
 impl Day7 {
-    pub fn load(filename: &str) -> Day7 {
-        let mut session: Vec<String> = Vec::new();
-
-        let file = File::open(filename).unwrap();
-        let reader = BufReader::new(file);
-
-        for line in reader.lines() {
-            let l = &line.unwrap();
-            session.push(l.to_string());
-        }
-
-        Day7 { session }
-    }
-
     fn run_session(&self) -> HashMap<String, usize> {
-        // TODO: Create hash of path -> size.
         // Keep a stack of paths between root and cwd.
         // When a file is encountered, add its size to all paths on the stack.
 
         let mut dir_sizes: HashMap<String, usize> = HashMap::new();
         let mut path_stack: Vec<String> = Vec::new();
 
-        lazy_static! {
-            static ref FILE_RE: Regex =
-                Regex::new(r"([0-9]+) ([a-zA-Z0-9\.]+)").unwrap();
-            static ref CD_RE: Regex = 
-                Regex::new(r"^\$ cd ([a-zA-Z0-1_]+)").unwrap();  // cap[1] is the directory being entered.
-        }
-
         // Session context
         let mut cwd = String::new();
         cwd += "/";
         dir_sizes.insert(cwd.to_string(), 0);
         path_stack.push(cwd.to_string());
 
-        for line in &self.session {
-            if line.starts_with("$ ls") {
-                // ls command (can ignore this)
-                ()
+        for entry in &self.session {
+            match entry {
+                Entry::Ls | Entry::Dir => {
+                    // ls command or a subdirectory listing (can ignore these)
+                }
+                Entry::CdRoot => {
+                    // cd to top dir
+                    cwd.clear();
+                    cwd += "/";
+                    path_stack.clear();
+                    path_stack.push(cwd.to_string());
+                }
+                Entry::CdUp => {
+                    // cd to parent dir
+                    path_stack.pop();
+                    cwd.clear();
+                    cwd += path_stack.last().unwrap();
+                }
+                Entry::Cd(subdir) => {
+                    // cd into a subdir
+                    cwd += subdir;
+                    cwd += "/";
+                    path_stack.push(cwd.to_string());
+                    dir_sizes.entry(cwd.to_string()).or_insert(0);
+                }
+                Entry::File(size) => {
+                    // this is a file, add its size to all parent directories in path_stack
+                    for path in &path_stack {
+                        *dir_sizes.get_mut(path).unwrap() += size;
+                    }
+                }
             }
-            else if line.starts_with("$ cd /") {
-                // cd to top dir
-                cwd.clear();
-                cwd += "/";
-                path_stack.clear();
-                path_stack.push(cwd.to_string());
-                // println!("cd to root");
+        }
+
+        dir_sizes
+    }
+}
+
+impl Day for Day7 {
+    fn parse(input: &Input) -> Result<Day7, AocError> {
+        let mut session: Vec<Entry> = Vec::new();
+
+        lazy_static! {
+            static ref FILE_RE: Regex =
+                Regex::new(r"([0-9]+) ([a-zA-Z0-9\.]+)").unwrap();
+            static ref CD_RE: Regex =
+                Regex::new(r"^\$ cd (\S+)").unwrap();  // cap[1] is the directory being entered.
+        }
+
+        // Track depth so a "cd .." out of the top directory is caught here.
+        let mut depth = 0;
+
+        for line in input.lines() {
+            let l = line.text;
+            if l.starts_with("$ ls") {
+                session.push(Entry::Ls);
             }
-            else if line.starts_with("$ cd ..") {
-                // cd to parent dir
-                path_stack.pop();
-                cwd.clear();
-                cwd += path_stack.last().unwrap();
-                // println!("cd ..")
+            else if l.starts_with("$ cd /") {
+                session.push(Entry::CdRoot);
+                depth = 0;
+            }
+            else if l.starts_with("$ cd ..") {
+                if depth == 0 {
+                    return Err(line.error(1, "cd .. from the top directory"));
+                }
+                session.push(Entry::CdUp);
+                depth -= 1;
             }
-            else if line.starts_with("$ cd ") {
-                let m = CD_RE.captures(line);
-                match m {
+            else if l.starts_with("$ cd ") {
+                match CD_RE.captures(l) {
                     Some(cap) => {
-                        let subdir = cap[1].to_string();
-                        cwd += &subdir;
-                        cwd += "/";
-                        // println!("cd into {subdir}, {cwd}");
-                        path_stack.push(cwd.to_string());
-                        if !dir_sizes.contains_key(&cwd) {
-                            dir_sizes.insert(cwd.to_string(), 0);
-                        }
+                        session.push(Entry::Cd(cap[1].to_string()));
+                        depth += 1;
                     }
                     None => {
-                        // println!("{line}");
-                        panic!("No subdir captured!");
+                        return Err(line.error(6, "no directory given to cd"));
                     }
                 }
-                // cd into a subdir
             }
-            else if line.starts_with("dir ") {
-                // a subdirectory has been observed. (ignore this)
+            else if l.starts_with("dir ") {
+                session.push(Entry::Dir);
             }
             else {
-                // this is a file, record it
-                // TODO-DW : Add size to all parent directories in pathStack
-                let m = FILE_RE.captures(&line);
-                match m {
+                match FILE_RE.captures(l) {
                     Some(cap) => {
-                        let size = cap[1].parse::<usize>().unwrap();
-                        let _name = cap[2].to_string();
-                        for path in &path_stack {
-                            *dir_sizes.get_mut(path).unwrap() += size;
-                            // println!("Adding {} to {}", size, path);
-                        }
+                        let size = line.field(&cap, 1)?;
+                        session.push(Entry::File(size));
                     }
-                    None => ()
+                    None => line.unmatched()?,
                 }
             }
-
         }
 
-        dir_sizes
+        Ok(Day7 { session })
     }
-}
 
-impl Day for Day7 {
     fn part1(&self) -> Answer {
         let dir_sizes = self.run_session();
         let mut sum = 0;
@@ -152,26 +169,26 @@ mod tests {
 
     #[test]
     fn test_load() {
-        let d = Day7::load("examples/day7_example1.txt");
+        let d = Day7::load("examples/day7_example1.txt").unwrap();
         assert_eq!(d.session.len(), 23);
     }
 
     #[test]
     fn test_run_session() {
-        let d = Day7::load("examples/day7_example1.txt");
+        let d = Day7::load("examples/day7_example1.txt").unwrap();
         let dir_sizes = d.run_session();
         assert_eq!(dir_sizes.len(), 4);
     }
 
     #[test]
     fn test_part1() {
-        let d = Day7::load("examples/day7_example1.txt");
+        let d = Day7::load("examples/day7_example1.txt").unwrap();
         assert_eq!(d.part1(), Answer::Number(95437));
     }
 
     #[test]
     fn test_part2() {
-        let d = Day7::load("examples/day7_example1.txt");
+        let d = Day7::load("examples/day7_example1.txt").unwrap();
         assert_eq!(d.part2(), Answer::Number(24933642));
     }
 }
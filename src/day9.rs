@@ -1,7 +1,8 @@
 use crate::day::{Day, Answer};
+use crate::error::AocError;
+use crate::grid::SparseGrid;
+use crate::input::Input;
 use std::collections::HashSet;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
 use lazy_static::lazy_static;
 use regex::Regex;
 
@@ -122,43 +123,15 @@ impl Sim {
     // for debugging.  Don't complain when not in use.
     #[allow(dead_code)]
     fn show_rope(&self) {
-        // get extents
-        let mut min_x = 0;
-        let mut max_x = 0;
-        let mut min_y = 0;
-        let mut max_y = 0;
-
-        for i in 0..self.num_knots {
-            let x = self.knots[i].0;
-            let y = self.knots[i].1;
-            if x < min_x { min_x = x; }
-            if x > max_x { max_x = x; }
-            if y < min_y { min_y = y; }
-            if y > max_y { max_y = y; }
+        // Knots are numbered from the head, and the start is marked 's'.  Where
+        // several share a square, show the one nearest the head.
+        let mut grid = SparseGrid::new();
+        grid.insert((0, 0), 's');
+        for (i, knot) in self.knots.iter().enumerate().rev() {
+            grid.insert(*knot, char::from_digit(i as u32, 36).unwrap());
         }
 
-        // print full extents
-        for y in (min_y ..= max_y).rev() {
-            for x in min_x ..= max_x {
-                let mut printed = false;
-                for i in 0..self.num_knots {
-                    if self.knots[i] == (x, y) {
-                        print!("{}", i);
-                        printed = true;
-                        break;
-                    }
-                }
-                if !printed {
-                    if (x,y) == (0, 0) {
-                        print!("s");
-                    }
-                    else {
-                        print!(".");
-                    }
-                }
-            }
-            println!();
-        }
+        print!("{}", grid.render(true, |cell| cell.copied().unwrap_or('.')));
         println!("---------------------------------------------------");
     }
     
@@ -223,21 +196,16 @@ pub struct Day9 {
     instructions: Vec<Instruction>,
 }
 
-impl Day9 {
-    pub fn load(filename: &str) -> Day9 {
+impl Day for Day9 {
+    fn parse(input: &Input) -> Result<Day9, AocError> {
         let mut instructions: Vec<Instruction> = Vec::new();
         lazy_static! {
             static ref LINE_RE: Regex =
                 Regex::new("([UDLR]) ([0-9]+)").unwrap();
         }
 
-        let file = File::open(filename).unwrap();
-        let reader = BufReader::new(file);
-
-        for line in reader.lines() {
-            let l = &line.unwrap();
-            let caps = LINE_RE.captures(&l);
-            match caps {
+        for line in input.lines() {
+            match LINE_RE.captures(line.text) {
                 Some(caps) => {
                     let dir = match &caps[1] {
                         "U" => Dir::Up,
@@ -246,19 +214,17 @@ impl Day9 {
                         "R" => Dir::Right,
                         _ => panic!("Invalid direction encountered.")
                     };
-                    let dist = caps[2].parse::<usize>().unwrap();
+                    let dist = line.field(&caps, 2)?;
                     instructions.push(Instruction {dir, dist});
                 }
-                None => {}
+                None => line.unmatched()?,
             }
 
         }
 
-        Day9 { instructions }
+        Ok(Day9 { instructions })
     }
-}
 
-impl Day for Day9 {
     fn part1(&self) -> Answer {
         let mut sim= Sim::new(2);
         sim.do_instructions(&self.instructions);
@@ -281,7 +247,7 @@ mod tests {
 
     #[test]
     fn test_load() {
-        let d = Day9::load("examples/day9_example1.txt");
+        let d = Day9::load("examples/day9_example1.txt").unwrap();
         assert_eq!(d.instructions.len(), 8);
     }
 
@@ -319,7 +285,7 @@ mod tests {
 
     #[test]
     fn test_example1() {
-        let d = Day9::load("examples/day9_example1.txt");
+        let d = Day9::load("examples/day9_example1.txt").unwrap();
 
         let mut sim = Sim::new(2);
         sim.do_instructions(&d.instructions);
@@ -329,14 +295,14 @@ mod tests {
 
     #[test]
     fn test_example1_part1() {
-        let d = Day9::load("examples/day9_example1.txt");
+        let d = Day9::load("examples/day9_example1.txt").unwrap();
 
         assert_eq!(d.part1(), Answer::Number(13));
     }
 
     #[test]
     fn test_example1_part2() {
-        let d = Day9::load("examples/day9_example1.txt");
+        let d = Day9::load("examples/day9_example1.txt").unwrap();
 
         let mut sim = Sim::new(10);
         sim.do_instructions(&d.instructions);
@@ -346,7 +312,7 @@ mod tests {
 
     #[test]
     fn test_example2_part2() {
-        let d = Day9::load("examples/day9_example2.txt");
+        let d = Day9::load("examples/day9_example2.txt").unwrap();
 
         let mut sim = Sim::new(10);
         sim.do_instructions(&d.instructions);
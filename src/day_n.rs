@@ -1,6 +1,6 @@
 use crate::day::{Day, Answer};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use crate::error::AocError;
+use crate::input::Input;
 use lazy_static::lazy_static;
 use regex::Regex;
 
@@ -8,34 +8,30 @@ pub struct DayN {
     tbd: Vec<usize>,
 }
 
-impl DayN {
-    pub fn load(filename: &str) -> DayN {
+// To add a day: copy this file to dayN.rs, add "mod dayN;" to main.rs and
+// register it in the DAYS table in registry.rs.
+
+impl Day for DayN {
+    fn parse(input: &Input) -> Result<DayN, AocError> {
         let mut tbd: Vec<usize> = Vec::new();
         lazy_static! {
             static ref LINE_RE: Regex =
                 Regex::new("([0-9]+)").unwrap();
         }
 
-        let file = File::open(filename).unwrap();
-        let reader = BufReader::new(file);
-
-        for line in reader.lines() {
-            let l = &line.unwrap();
-            let caps = LINE_RE.captures(&l);
-            match caps {
+        for line in input.lines() {
+            match LINE_RE.captures(line.text) {
                 Some(caps) => {
-                    let n = caps[1].parse::<usize>().unwrap();
+                    let n = line.field(&caps, 1)?;
                     tbd.push(n);
                 }
-                None => {}
+                None => line.unmatched()?,
             }
         }
 
-        DayN { tbd: tbd }
+        Ok(DayN { tbd })
     }
-}
 
-impl Day for DayN {
     fn part1(&self) -> Answer {
         Answer::Number(1)
     }
@@ -52,7 +48,7 @@ mod tests {
 
     #[test]
     fn test_load() {
-        let d = DayN::load("examples/dayN_example1.txt");
+        let d = DayN::load("examples/dayn_example1.txt").unwrap();
         assert_eq!(d.tbd.len(), 10);
     }
 }
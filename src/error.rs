@@ -0,0 +1,50 @@
+use std::fmt;
+use std::io;
+
+// Everything that can go wrong while reading a puzzle input.
+#[derive(Debug)]
+pub enum AocError {
+    // The input couldn't be read at all.
+    Io { file: String, error: io::Error },
+    // A line couldn't be parsed.  line and column are 1-based.
+    Parse { file: String, line: usize, column: usize, message: String },
+    // The input parsed, but doesn't describe a puzzle we can solve.
+    Invalid { file: String, message: String },
+}
+
+impl fmt::Display for AocError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AocError::Io { file, error } => write!(f, "{}: {}", file, error),
+            AocError::Parse { file, line, column, message } => {
+                write!(f, "{}:{}:{}: {}", file, line, column, message)
+            }
+            AocError::Invalid { file, message } => write!(f, "{}: {}", file, message),
+        }
+    }
+}
+
+impl std::error::Error for AocError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AocError::Io { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let e = AocError::Parse {
+            file: "day4.txt".to_string(), line: 3, column: 7, message: "bad number".to_string()
+        };
+        assert_eq!(e.to_string(), "day4.txt:3:7: bad number");
+
+        let e = AocError::Invalid { file: "day12.txt".to_string(), message: "no start".to_string() };
+        assert_eq!(e.to_string(), "day12.txt: no start");
+    }
+}
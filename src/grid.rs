@@ -0,0 +1,286 @@
+use std::collections::HashMap;
+use std::ops::{Index, IndexMut};
+use crate::error::AocError;
+use crate::input::Input;
+
+// Two dimensional maps.
+//
+// Grid is a dense, fixed size rectangle, like the maps most puzzles give as
+// lines of characters.  Positions are (row, col), counting from the top left.
+//
+// SparseGrid holds cells scattered over an unbounded plane, like a rope's
+// path or a cave built up from line segments, and keeps track of how far
+// they extend.  Positions are (x, y), as the puzzles give them.
+
+// Steps to the four neighbours, as (row, col) offsets: up, down, left, right.
+pub const DIRECTIONS4: [(isize, isize); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,      // row-major
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Grid<T>
+        where T: Clone
+    {
+        Grid { width, height, cells: vec![fill; width * height] }
+    }
+
+    // Rows must all be the same length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Grid<T> {
+        let height = rows.len();
+        let width = rows.first().map_or(0, |row| row.len());
+        assert!(rows.iter().all(|row| row.len() == width), "grid rows differ in length");
+
+        Grid { width, height, cells: rows.into_iter().flatten().collect() }
+    }
+
+    // Read a map drawn one row per line.  cell turns each character into a
+    // cell, or None if it doesn't belong in a map; what names the map in
+    // error messages.  Blank lines are ignored.
+    pub fn parse<F>(input: &Input, what: &str, mut cell: F) -> Result<Grid<T>, AocError>
+        where F: FnMut(char) -> Option<T>
+    {
+        let mut rows: Vec<Vec<T>> = Vec::new();
+        for line in input.lines() {
+            let text = line.text.trim();
+            if text.is_empty() {
+                continue;
+            }
+            let mut row: Vec<T> = Vec::new();
+            for (col, c) in text.chars().enumerate() {
+                match cell(c) {
+                    Some(value) => row.push(value),
+                    None => return Err(line.error(col + 1, &format!("unexpected character '{}' in {}", c, what))),
+                }
+            }
+            if !rows.is_empty() && row.len() != rows[0].len() {
+                return Err(line.error(1, &format!("expected {} columns, found {}", rows[0].len(), row.len())));
+            }
+            rows.push(row);
+        }
+
+        if rows.is_empty() {
+            return Err(input.invalid(&format!("no {} found", what)));
+        }
+
+        Ok(Grid::from_rows(rows))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, (row, col): (usize, usize)) -> bool {
+        row < self.height && col < self.width
+    }
+
+    pub fn get(&self, position: (usize, usize)) -> Option<&T> {
+        if self.contains(position) {
+            Some(&self.cells[position.0 * self.width + position.1])
+        }
+        else {
+            None
+        }
+    }
+
+    // The position one step of (d_row, d_col) away, if that's still on the grid.
+    pub fn step(&self, (row, col): (usize, usize), (d_row, d_col): (isize, isize)) -> Option<(usize, usize)> {
+        let row = row.checked_add_signed(d_row)?;
+        let col = col.checked_add_signed(d_col)?;
+        if self.contains((row, col)) { Some((row, col)) } else { None }
+    }
+
+    // Every position, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |row| (0..width).map(move |col| (row, col)))
+    }
+
+    // Every position with its cell, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    // Positions of the cells that match.
+    pub fn find<'a, P>(&'a self, mut matches: P) -> impl Iterator<Item = (usize, usize)> + 'a
+        where P: FnMut(&T) -> bool + 'a
+    {
+        self.iter().filter(move |(_, cell)| matches(cell)).map(|(position, _)| position)
+    }
+
+    // The up, down, left and right neighbours that are on the grid.
+    pub fn neighbours4(&self, position: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        DIRECTIONS4.iter().filter_map(move |&direction| self.step(position, direction))
+    }
+
+    pub fn row(&self, row: usize) -> impl Iterator<Item = &T> {
+        self.cells[row * self.width..(row + 1) * self.width].iter()
+    }
+
+    // The positions from position (not included) to the edge, in direction.
+    pub fn ray(&self, position: (usize, usize), direction: (isize, isize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let mut current = position;
+        std::iter::from_fn(move || {
+            current = self.step(current, direction)?;
+            Some(current)
+        })
+    }
+
+    pub fn map<U, F>(&self, f: F) -> Grid<U>
+        where F: FnMut(&T) -> U
+    {
+        Grid { width: self.width, height: self.height, cells: self.cells.iter().map(f).collect() }
+    }
+
+    // One line of text per row, each ending in a newline.
+    pub fn render<F>(&self, mut draw: F) -> String
+        where F: FnMut(&T) -> char
+    {
+        let mut text = String::new();
+        for row in 0..self.height {
+            text.extend(self.row(row).map(&mut draw));
+            text.push('\n');
+        }
+
+        text
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (row, col): (usize, usize)) -> &T {
+        assert!(self.contains((row, col)), "({}, {}) is off the {}x{} grid", row, col, self.width, self.height);
+        &self.cells[row * self.width + col]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut T {
+        assert!(self.contains((row, col)), "({}, {}) is off the {}x{} grid", row, col, self.width, self.height);
+        &mut self.cells[row * self.width + col]
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SparseGrid<T> {
+    cells: HashMap<(isize, isize), T>,
+    extents: Option<((isize, isize), (isize, isize))>,    // ((min x, min y), (max x, max y))
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> SparseGrid<T> {
+        SparseGrid { cells: HashMap::new(), extents: None }
+    }
+
+    // Returns the cell's previous value, if any.
+    pub fn insert(&mut self, (x, y): (isize, isize), value: T) -> Option<T> {
+        self.extents = Some(match self.extents {
+            None => ((x, y), (x, y)),
+            Some(((min_x, min_y), (max_x, max_y))) => ((min_x.min(x), min_y.min(y)), (max_x.max(x), max_y.max(y))),
+        });
+
+        self.cells.insert((x, y), value)
+    }
+
+    pub fn get(&self, position: (isize, isize)) -> Option<&T> {
+        self.cells.get(&position)
+    }
+
+    // Everything within the extents, one line per row, with y increasing
+    // downwards or, if y_up, upwards.  draw is given None for empty cells.
+    pub fn render<F>(&self, y_up: bool, mut draw: F) -> String
+        where F: FnMut(Option<&T>) -> char
+    {
+        let mut text = String::new();
+        if let Some(((min_x, min_y), (max_x, max_y))) = self.extents {
+            let rows: Vec<isize> = if y_up { (min_y..=max_y).rev().collect() } else { (min_y..=max_y).collect() };
+            for y in rows {
+                text.extend((min_x..=max_x).map(|x| draw(self.get((x, y)))));
+                text.push('\n');
+            }
+        }
+
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digits() -> Grid<u32> {
+        let input = Input::from_text("123\n456\n");
+        Grid::parse(&input, "digits", |c| c.to_digit(10)).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let grid = digits();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(1, 0)], 4);
+        assert_eq!(grid.get((1, 2)), Some(&6));
+        assert_eq!(grid.get((2, 0)), None);
+        assert_eq!(grid.get((0, 3)), None);
+        assert_eq!(grid.find(|&d| d % 2 == 0).collect::<Vec<_>>(), vec![(0, 1), (1, 0), (1, 2)]);
+
+        let input = Input::from_text("12\n3x\n");
+        let e = Grid::parse(&input, "digits", |c| c.to_digit(10)).unwrap_err();
+        assert!(e.to_string().contains(":2:2:"), "{}", e);
+        let input = Input::from_text("12\n345\n");
+        assert!(Grid::parse(&input, "digits", |c| c.to_digit(10)).is_err());
+        assert!(Grid::parse(&Input::from_text(""), "digits", |c| c.to_digit(10)).is_err());
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = digits();
+        let mut corner: Vec<_> = grid.neighbours4((0, 0)).collect();
+        corner.sort();
+        assert_eq!(corner, vec![(0, 1), (1, 0)]);
+        assert_eq!(grid.neighbours4((1, 1)).count(), 3);
+        assert_eq!(grid.step((0, 0), (-1, 0)), None);
+        assert_eq!(grid.step((0, 0), (1, 1)), Some((1, 1)));
+    }
+
+    #[test]
+    fn test_lines() {
+        let grid = digits();
+        assert_eq!(grid.row(1).copied().collect::<Vec<_>>(), vec![4, 5, 6]);
+        assert_eq!(grid.ray((1, 0), (0, 1)).collect::<Vec<_>>(), vec![(1, 1), (1, 2)]);
+        assert_eq!(grid.ray((1, 0), (-1, 1)).collect::<Vec<_>>(), vec![(0, 1)]);
+        assert_eq!(grid.ray((0, 0), (-1, 0)).count(), 0);
+    }
+
+    #[test]
+    fn test_render() {
+        let grid = digits();
+        let draw = |d: &u32| char::from_digit(*d, 10).unwrap();
+        assert_eq!(grid.render(draw), "123\n456\n");
+        assert_eq!(grid.map(|d| d * 2)[(1, 2)], 12);
+    }
+
+    #[test]
+    fn test_sparse() {
+        let mut grid: SparseGrid<char> = SparseGrid::new();
+        assert_eq!(grid.render(false, |_| '.'), "");
+
+        grid.insert((0, 0), 'a');
+        grid.insert((2, -1), 'b');
+        assert_eq!(grid.insert((0, 0), 'c'), Some('a'));
+        assert_eq!(grid.get((2, -1)), Some(&'b'));
+        assert_eq!(grid.get((1, 0)), None);
+
+        let draw = |cell: Option<&char>| cell.copied().unwrap_or('.');
+        assert_eq!(grid.render(false, draw), "..b\nc..\n");
+        assert_eq!(grid.render(true, draw), "c..\n..b\n");
+    }
+}
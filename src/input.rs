@@ -0,0 +1,173 @@
+use crate::error::AocError;
+use regex::Captures;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::str::FromStr;
+
+// A puzzle input, read into memory along with where it came from.
+//
+// In strict mode, lines that a day's parser doesn't recognize are errors.
+// Otherwise they are skipped, as they always have been.
+pub struct Input {
+    file: String,
+    lines: Vec<String>,
+    strict: bool,
+}
+
+// One line of an Input, with enough context to report errors against it.
+pub struct Line<'a> {
+    input: &'a Input,
+    pub number: usize,  // 1-based
+    pub text: &'a str,
+}
+
+impl Input {
+    pub fn open(filename: &str) -> Result<Input, AocError> {
+        match File::open(filename) {
+            Ok(file) => Input::from_reader(filename, BufReader::new(file)),
+            Err(error) => Err(AocError::Io { file: filename.to_string(), error }),
+        }
+    }
+
+    // Read an input from any reader, e.g. stdin.  name is used in error messages.
+    pub fn from_reader<R: BufRead>(name: &str, reader: R) -> Result<Input, AocError> {
+        let mut lines: Vec<String> = Vec::new();
+        for line in reader.lines() {
+            match line {
+                Ok(line) => lines.push(line),
+                Err(error) => return Err(AocError::Io { file: name.to_string(), error }),
+            }
+        }
+
+        Ok(Input { file: name.to_string(), lines, strict: false })
+    }
+
+    // An input held in a string, e.g. an example embedded in a test.
+    pub fn from_text(text: &str) -> Input {
+        let lines = text.lines().map(|l| l.to_string()).collect();
+
+        Input { file: "<string>".to_string(), lines, strict: false }
+    }
+
+    pub fn strict(mut self, strict: bool) -> Input {
+        self.strict = strict;
+        self
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = Line<'_>> {
+        self.lines.iter().enumerate().map(move |(n, text)| Line { input: self, number: n + 1, text })
+    }
+
+    // The first line of a single-line input.
+    pub fn first_line(&self) -> Result<Line<'_>, AocError> {
+        match self.lines().next() {
+            Some(line) => Ok(line),
+            None => Err(self.invalid("input is empty")),
+        }
+    }
+
+    // A problem with the input as a whole rather than with one line.
+    pub fn invalid(&self, message: &str) -> AocError {
+        AocError::Invalid { file: self.file.clone(), message: message.to_string() }
+    }
+}
+
+impl<'a> Line<'a> {
+    pub fn error(&self, column: usize, message: &str) -> AocError {
+        AocError::Parse {
+            file: self.input.file.clone(),
+            line: self.number,
+            column,
+            message: message.to_string(),
+        }
+    }
+
+    // Parse capture group n (captured from this line's text), reporting
+    // its column if it doesn't parse.
+    pub fn field<T>(&self, caps: &Captures, n: usize) -> Result<T, AocError>
+    where T: FromStr, T::Err: Display {
+        match caps.get(n) {
+            Some(m) => m.as_str().parse::<T>().map_err(|e| {
+                self.error(m.start() + 1, &format!("invalid value '{}': {}", m.as_str(), e))
+            }),
+            None => Err(self.error(1, &format!("missing field {}", n))),
+        }
+    }
+
+    // Called for a line the parser didn't recognize.  Blank lines are
+    // always allowed; anything else is an error in strict mode.
+    pub fn unmatched(&self) -> Result<(), AocError> {
+        if self.input.strict && !self.text.trim().is_empty() {
+            Err(self.error(1, &format!("unrecognized line '{}'", self.text)))
+        }
+        else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex::Regex;
+
+    #[test]
+    fn test_lines() {
+        let input = Input::from_text("one\n\nthree\n");
+        let lines: Vec<(usize, &str)> = input.lines().map(|l| (l.number, l.text)).collect();
+        assert_eq!(lines, vec![(1, "one"), (2, ""), (3, "three")]);
+        assert_eq!(input.first_line().unwrap().text, "one");
+        assert!(Input::from_text("").first_line().is_err());
+    }
+
+    #[test]
+    fn test_field() {
+        let re = Regex::new("([a-z]+)=([0-9]+)").unwrap();
+        let input = Input::from_text("x=12\nyy=99999999999999999999999");
+        let lines: Vec<Line> = input.lines().collect();
+
+        let caps = re.captures(lines[0].text).unwrap();
+        assert_eq!(lines[0].field::<usize>(&caps, 2).unwrap(), 12);
+
+        let caps = re.captures(lines[1].text).unwrap();
+        match lines[1].field::<usize>(&caps, 2) {
+            Err(AocError::Parse { line, column, .. }) => assert_eq!((line, column), (2, 4)),
+            _ => panic!("expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn test_unmatched() {
+        let input = Input::from_text("junk\n ");
+        for line in input.lines() {
+            assert!(line.unmatched().is_ok());
+        }
+
+        let input = input.strict(true);
+        let lines: Vec<Line> = input.lines().collect();
+        assert!(lines[0].unmatched().is_err());
+        assert!(lines[1].unmatched().is_ok());
+    }
+
+    #[test]
+    fn test_from_reader() {
+        let reader = std::io::Cursor::new("one\r\ntwo\n");
+        let input = Input::from_reader("<cursor>", reader).unwrap();
+        let lines: Vec<&str> = input.lines().map(|l| l.text).collect();
+        assert_eq!(lines, vec!["one", "two"]);
+
+        match input.first_line().unwrap().error(2, "oops") {
+            AocError::Parse { file, .. } => assert_eq!(file, "<cursor>"),
+            _ => panic!("expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn test_missing_file() {
+        match Input::open("examples/no_such_file.txt") {
+            Err(AocError::Io { file, .. }) => assert_eq!(file, "examples/no_such_file.txt"),
+            _ => panic!("expected an io error"),
+        }
+    }
+}
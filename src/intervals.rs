@@ -0,0 +1,206 @@
+// A set of integers held as sorted, disjoint, inclusive ranges.
+//
+// Ranges that touch or overlap are merged, so two sets holding the same
+// integers are always equal.  Everything costs time in the number of ranges,
+// not the number of integers, so sets can span huge coordinates.
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct IntervalSet {
+    ranges: Vec<(isize, isize)>,    // (low, high), sorted, with gaps between
+}
+
+impl IntervalSet {
+    pub fn new() -> IntervalSet {
+        IntervalSet { ranges: Vec::new() }
+    }
+
+    // low..=high, or nothing if high < low.
+    pub fn single(low: isize, high: isize) -> IntervalSet {
+        let mut set = IntervalSet::new();
+        set.insert(low, high);
+        set
+    }
+
+    // Add low..=high.
+    pub fn insert(&mut self, low: isize, high: isize) {
+        if high < low {
+            return;
+        }
+        // The ranges before low..=high that don't touch it, and those after.
+        let before = self.ranges.partition_point(|&(_, h)| h < low - 1);
+        let after = self.ranges.partition_point(|&(l, _)| l <= high + 1);
+        let mut merged = (low, high);
+        if before < after {
+            merged.0 = merged.0.min(self.ranges[before].0);
+            merged.1 = merged.1.max(self.ranges[after - 1].1);
+        }
+        self.ranges.splice(before..after, [merged]);
+    }
+
+    // The ranges, in order.
+    pub fn ranges(&self) -> &[(isize, isize)] {
+        &self.ranges
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    // Number of integers in the set.
+    pub fn len(&self) -> usize {
+        self.ranges.iter().map(|(low, high)| (high - low + 1) as usize).sum()
+    }
+
+    // true if every integer in other is also in this set.
+    pub fn contains_set(&self, other: &IntervalSet) -> bool {
+        other.subtract(self).is_empty()
+    }
+
+    pub fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        // Walk both lists together, keeping the overlap of each pair.
+        let mut ranges = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a, b) = (self.ranges[i], other.ranges[j]);
+            let low = a.0.max(b.0);
+            let high = a.1.min(b.1);
+            if low <= high {
+                ranges.push((low, high));
+            }
+            if a.1 < b.1 {
+                i += 1;
+            }
+            else {
+                j += 1;
+            }
+        }
+
+        IntervalSet { ranges }
+    }
+
+    // This set without anything in other.
+    pub fn subtract(&self, other: &IntervalSet) -> IntervalSet {
+        let mut ranges = Vec::new();
+        let mut j = 0;
+        for &(low, high) in &self.ranges {
+            let mut low = low;
+            // Skip the ranges of other that end before this one starts.
+            while j < other.ranges.len() && other.ranges[j].1 < low {
+                j += 1;
+            }
+            let mut k = j;
+            while k < other.ranges.len() && other.ranges[k].0 <= high {
+                let (cut_low, cut_high) = other.ranges[k];
+                if cut_low > low {
+                    ranges.push((low, cut_low - 1));
+                }
+                low = cut_high + 1;
+                k += 1;
+            }
+            if low <= high {
+                ranges.push((low, high));
+            }
+        }
+
+        IntervalSet { ranges }
+    }
+
+    // What's missing from low..=high.
+    pub fn gaps(&self, low: isize, high: isize) -> IntervalSet {
+        IntervalSet::single(low, high).subtract(self)
+    }
+}
+
+// Collect ranges, in any order, overlapping or not.  Sorting first makes
+// this O(n log n) in the number of ranges.
+impl FromIterator<(isize, isize)> for IntervalSet {
+    fn from_iter<I: IntoIterator<Item = (isize, isize)>>(iter: I) -> IntervalSet {
+        let mut sorted: Vec<(isize, isize)> = iter.into_iter().filter(|(low, high)| low <= high).collect();
+        sorted.sort();
+
+        let mut ranges: Vec<(isize, isize)> = Vec::new();
+        for (low, high) in sorted {
+            match ranges.last_mut() {
+                Some(last) if low <= last.1 + 1 => last.1 = last.1.max(high),
+                _ => ranges.push((low, high)),
+            }
+        }
+
+        IntervalSet { ranges }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::random_cases;
+    use std::collections::BTreeSet;
+
+    fn points(set: &IntervalSet) -> BTreeSet<isize> {
+        set.ranges().iter().flat_map(|&(low, high)| low..=high).collect()
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut set = IntervalSet::new();
+        set.insert(5, 7);
+        set.insert(1, 2);
+        set.insert(10, 12);
+        assert_eq!(set.ranges(), [(1, 2), (5, 7), (10, 12)]);
+        assert_eq!(set.len(), 8);
+
+        // Adjacent ranges merge, and one range can swallow several.
+        set.insert(3, 4);
+        assert_eq!(set.ranges(), [(1, 7), (10, 12)]);
+        set.insert(0, 20);
+        assert_eq!(set.ranges(), [(0, 20)]);
+        set.insert(4, 2);
+        assert_eq!(set.ranges(), [(0, 20)]);
+
+        assert_eq!(set.gaps(-1, 21).ranges(), [(-1, -1), (21, 21)]);
+    }
+
+    #[test]
+    fn test_operations() {
+        let a: IntervalSet = [(0, 10), (20, 30)].into_iter().collect();
+        let b: IntervalSet = [(5, 25), (40, 40)].into_iter().collect();
+        assert_eq!(a.intersection(&b).ranges(), [(5, 10), (20, 25)]);
+        assert_eq!(a.subtract(&b).ranges(), [(0, 4), (26, 30)]);
+        assert_eq!(b.subtract(&a).ranges(), [(11, 19), (40, 40)]);
+        assert_eq!(a.gaps(-5, 35).ranges(), [(-5, -1), (11, 19), (31, 35)]);
+        assert!(a.contains_set(&IntervalSet::single(21, 29)));
+        assert!(!a.contains_set(&b));
+        assert!(a.contains_set(&IntervalSet::new()));
+    }
+
+    #[test]
+    fn test_random_sets() {
+        // Compare against plain sets of integers.
+        random_cases(17, 200, |next| {
+            let mut random_set = || -> IntervalSet {
+                (0..next(5)).map(|_| {
+                    let low = next(40) as isize - 20;
+                    (low, low + next(8) as isize)
+                }).collect()
+            };
+            let (a, b) = (random_set(), random_set());
+            let (pa, pb) = (points(&a), points(&b));
+
+            assert_eq!(a.len(), pa.len());
+            assert_eq!(points(&a.intersection(&b)), &pa & &pb);
+            assert_eq!(points(&a.subtract(&b)), &pa - &pb);
+            assert_eq!(a.contains_set(&b), pb.is_subset(&pa));
+
+            let mut inserted = a.clone();
+            for &(low, high) in b.ranges() {
+                inserted.insert(low, high);
+            }
+            assert_eq!(points(&inserted), &pa | &pb);
+
+            // Ranges are kept apart, so equal sets are equal.
+            for pair in a.ranges().windows(2) {
+                assert!(pair[0].1 + 1 < pair[1].0);
+            }
+        });
+    }
+}
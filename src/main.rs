@@ -1,7 +1,9 @@
 extern crate core;
 
-#[macro_use]
-
+mod answers;
+mod astar;
+mod branch_bound;
+mod cli;
 mod day;
 mod day1;
 mod day2;
@@ -21,237 +23,328 @@ mod day15;
 mod day16;
 mod day17;
 mod day18;
-// mod astar;
-// mod astar2;
+mod error;
+mod grid;
+mod input;
+mod intervals;
+mod observer;
+mod ocr;
+mod output;
+mod registry;
+#[cfg(test)]
+mod testutil;
+mod timing;
+mod voxel;
 
-use day::{Day, Answer};
-use day1::Day1;
-use day2::Day2;
-use day3::Day3;
-use day4::Day4;
-use day5::Day5;
-use day6::Day6;
-use day7::Day7;
-use day8::Day8;
-use day9::Day9;
-use day10::Day10;
-use day11::Day11;
-use day12::Day12;
-use day13::Day13;
-use day14::Day14;
-use day15::Day15;
-use day16::Day16;
-use day17::Day17;
-use day18::Day18;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
 
-fn do_day(n: usize, day: &dyn Day) {
-    match day.part1() {
-        Answer::None => println!("day {}, part 1: No Answer", n),
-        Answer::Number(val) => println!("day {}, part 1: {}", n, val),
-        Answer::Message(s) => println!("day {}, part 1: \n{}", n, s),
-    }
-    match day.part2() {
-        Answer::None => println!("day {}, part 2: No Answer", n),
-        Answer::Number(val) => println!("day {}, part 2: {}", n, val),
-        Answer::Message(s) => println!("day {}, part 2: \n{}", n, s),
-    }
+use answers::{Answers, Verdict};
+use cli::Options;
+use error::AocError;
+use input::Input;
+use output::{Format, Record};
+use registry::DayInfo;
+use timing::DayTiming;
+
+// Everything the runner learns about one day.
+struct DayResult {
+    records: Vec<Record>,
+    timing: DayTiming,
 }
 
-fn main() {
-    println!("Advent of Code 2022!");
-    println!("See adventofcode.com/2022 for background.");
-    println!("");
+// Parse and solve one day, timing each stage over options.repeat runs, and
+// export its picture if asked.  With more than one job, the two parts are
+// solved at the same time.
+fn do_day(info: &DayInfo, input: &Input, options: &Options, answers: Option<&Answers>)
+          -> Result<DayResult, AocError> {
+    let n = info.number;
+    let (day, parse) = timing::repeat(options.repeat, || (info.parse)(input));
+    let mut day = day?;
+    if let Some(variant) = &options.variant {
+        day.set_variant(variant).map_err(|message| input.invalid(&message))?;
+    }
+    let day = day.as_ref();
 
-    let day1 = Day1::load("data_aoc2022/day1_input.txt");
-    let day2 = Day2::load("data_aoc2022/day2_input.txt");
-    let day3 = Day3::load("data_aoc2022/day3_input.txt");
-    let day4 = Day4::load("data_aoc2022/day4_input.txt");
-    let day5 = Day5::load("data_aoc2022/day5_input.txt");   
-    let day6 = Day6::load("data_aoc2022/day6_input.txt");
-    let day7 = Day7::load("data_aoc2022/day7_input.txt");
-    let day8 = Day8::load("data_aoc2022/day8_input.txt");
-    let day9 = Day9::load("data_aoc2022/day9_input.txt");
-    let day10 = Day10::load("data_aoc2022/day10_input.txt");
-    let day11 = Day11::load("data_aoc2022/day11_input.txt");
-    let day12 = Day12::load("data_aoc2022/day12_input.txt");
-    let day13 = Day13::load("data_aoc2022/day13_input.txt");
-    let day14 = Day14::load("data_aoc2022/day14_input.txt");
-    let day15 = Day15::load("data_aoc2022/day15_input.txt");
-    let day16 = Day16::load("data_aoc2022/day16_input.txt");
-    let day17 = Day17::load("data_aoc2022/day17_input.txt");
-    let day18 = Day18::load("data_aoc2022/day18_input.txt");
+    if let Some(filename) = &options.export {
+        let format = filename.rsplit_once('.').map_or("", |(_, extension)| extension);
+        let picture = day.export(format, &options.export_options)
+            .map_err(|message| AocError::Invalid { file: filename.clone(), message })?;
+        fs::write(filename, picture)
+            .map_err(|error| AocError::Io { file: filename.clone(), error })?;
+    }
 
-    let days: Vec<&dyn Day> = vec![
-        &day1, 
-        &day2,
-        &day3,
-        &day4,
-        &day5,
-        &day6,
-        &day7,
-        &day8,
-        &day9,
-        &day10,
-        &day11,
-        &day12,
-        &day13,
-        &day14,
-        &day15,
-        &day16,
-        &day17,
-        &day18,
-    ];
+    let solve = |part: usize| {
+        if !options.wants_part(part) {
+            return None;
+        }
+        Some(timing::repeat(options.repeat, || {
+            if part == 1 { day.part1() } else { day.part2() }
+        }))
+    };
+    let (part1, part2) = if options.jobs > 1 {
+        thread::scope(|s| {
+            let part2 = s.spawn(|| solve(2));
+            (solve(1), part2.join().unwrap())
+        })
+    }
+    else {
+        (solve(1), solve(2))
+    };
 
-    let selected_day: Option<usize> = None;
-    match selected_day {
-        None => {
-            // No day selected, do them all
-            for (n, day) in days.iter().enumerate() {
-                do_day(n + 1, *day);
+    let mut records: Vec<Record> = Vec::new();
+    let mut timing = DayTiming { day: n, parse, part1: None, part2: None };
+    for (part, solved) in [(1, part1), (2, part2)] {
+        if let Some((answer, samples)) = solved {
+            let answer = if options.detail { answer } else { answer.without_detail() };
+            let answer = ocr::read_letters(answer);
+            let verdict = answers.map(|a| a.verify(&options.input_set(), n, part, &answer));
+            records.push(Record { day: n, part, answer, time: samples.median(), verdict });
+            if part == 1 {
+                timing.part1 = Some(samples);
+            }
+            else {
+                timing.part2 = Some(samples);
             }
-        }
-        Some(n) => {
-            do_day(n - 1, days[n - 1]);
         }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    Ok(DayResult { records, timing })
+}
 
-    // Test results will be different for each participant.
-    #[test]
-    fn test_day1() {
-        let d = Day1::load("data_aoc2022/day1_input.txt");
-        assert_eq!(d.part1(), Answer::Number(71780));
-        assert_eq!(d.part2(), Answer::Number(212489));
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let options = match Options::parse(&args) {
+        Ok(options) => options,
+        Err(msg) => {
+            eprintln!("{}", msg);
+            eprintln!("{}", cli::USAGE);
+            process::exit(2);
+        }
+    };
+    if options.help {
+        println!("{}", cli::USAGE);
+        return;
     }
-
-    #[test]
-    fn test_day2() {
-        let d = Day2::load("data_aoc2022/day2_input.txt");
-        assert_eq!(d.part1(), Answer::Number(13565));
-        assert_eq!(d.part2(), Answer::Number(12424));
+    if options.list {
+        for info in registry::DAYS {
+            if info.variants.is_empty() {
+                println!("day {:2}: {}", info.number, info.title);
+            }
+            else {
+                println!("day {:2}: {} (variants: {})", info.number, info.title, info.variants.join(", "));
+            }
+        }
+        return;
     }
 
-    #[test]
-    fn test_day3() {
-        let d = Day3::load("data_aoc2022/day3_input.txt");
-        assert_eq!(d.part1(), Answer::Number(8153));
-        assert_eq!(d.part2(), Answer::Number(2342));
+    // Resolve the selection against the registry.  No day selected means do them all.
+    let selected_days: Vec<&DayInfo> = match &options.days {
+        Some(days) => {
+            let mut selected = Vec::new();
+            for n in days {
+                match registry::find(*n) {
+                    Some(info) => selected.push(info),
+                    None => {
+                        eprintln!("Day {} is not implemented.", n);
+                        process::exit(2);
+                    }
+                }
+            }
+            selected
+        }
+        None => registry::DAYS.iter().collect(),
+    };
+    if let Some(variant) = &options.variant {
+        for info in &selected_days {
+            if !info.variants.contains(&variant.as_str()) {
+                eprintln!("Day {} has no variant '{}'.", info.number, variant);
+                process::exit(2);
+            }
+        }
     }
 
-    #[test]
-    fn test_day4() {
-        let d = Day4::load("data_aoc2022/day4_input.txt");
-        assert_eq!(d.part1(), Answer::Number(459));
-        assert_eq!(d.part2(), Answer::Number(779));
+    // Read all the inputs up front so we don't fail halfway through a run.
+    let mut inputs: Vec<(&DayInfo, Input)> = Vec::new();
+    for info in selected_days {
+        let filename = options.input_path(info.number);
+        let input = if filename == "-" {
+            Input::from_reader("<stdin>", io::stdin().lock())
+        }
+        else {
+            Input::open(&filename)
+        };
+        match input {
+            Ok(input) => inputs.push((info, input.strict(options.strict))),
+            Err(e) => {
+                eprintln!("day {}: {}", info.number, e);
+                process::exit(1);
+            }
+        }
     }
 
-    #[test]
-    fn test_day5() {
-        let d = Day5::load("data_aoc2022/day5_input.txt");
-        assert_eq!(d.part1(), Answer::Message("SHMSDGZVC".to_string()));
-        assert_eq!(d.part2(), Answer::Message("VRZGHDFBQ".to_string()));
+    let answers = if options.verify {
+        match Answers::load(&options.answers) {
+            Ok(answers) => Some(answers),
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        }
     }
+    else {
+        None
+    };
 
-    #[test]
-    fn test_day6() {
-        let d = Day6::load("data_aoc2022/day6_input.txt");
-        assert_eq!(d.part1(), Answer::Number(1287));
-        assert_eq!(d.part2(), Answer::Number(3716));
+    if let Some(filename) = &options.trace {
+        if let Err(e) = observer::trace_to(filename) {
+            eprintln!("{}: {}", filename, e);
+            process::exit(1);
+        }
     }
 
-    #[test]
-    fn test_day7() {
-        let d = Day7::load("data_aoc2022/day7_input.txt");
-        assert_eq!(d.part1(), Answer::Number(1667443));
-        assert_eq!(d.part2(), Answer::Number(8998590));
+    // Only the text format gets the banner; the others are for scripts.
+    if options.format == Format::Text {
+        println!("Advent of Code 2022!");
+        println!("See adventofcode.com/2022 for background.");
+        println!();
     }
-
-    #[test]
-    fn test_day8() {
-        let d = Day8::load("data_aoc2022/day8_input.txt");
-        assert_eq!(d.part1(), Answer::Number(1787));
-        assert_eq!(d.part2(), Answer::Number(440640));
+    else if let Some(header) = options.format.header() {
+        println!("{}", header);
     }
 
-    #[test]
-    fn test_day9() {
-        let d = Day9::load("data_aoc2022/day9_input.txt");
-        assert_eq!(d.part1(), Answer::Number(6090));
-        assert_eq!(d.part2(), Answer::Number(2566));
-    }
+    // Solve the days on options.jobs worker threads, but report them in order,
+    // each as soon as it and every day before it are done.  A day whose input
+    // doesn't parse is reported, and the rest still run.
+    let mut failed = false;
+    let mut timings: Vec<DayTiming> = Vec::new();
+    let mut verdicts: Vec<Verdict> = Vec::new();
+    let (sender, receiver) = mpsc::channel();
+    let next_job = AtomicUsize::new(0);
+    thread::scope(|s| {
+        for _ in 0..options.jobs.min(inputs.len()) {
+            let sender = sender.clone();
+            let (inputs, next_job, options, answers) = (&inputs, &next_job, &options, answers.as_ref());
+            s.spawn(move || loop {
+                let i = next_job.fetch_add(1, Ordering::Relaxed);
+                if i >= inputs.len() {
+                    break;
+                }
+                let (info, input) = &inputs[i];
+                sender.send((i, do_day(info, input, options, answers))).unwrap();
+            });
+        }
+        drop(sender);
 
-    #[test]
-    fn test_day10() {
-        let d = Day10::load("data_aoc2022/day10_input.txt");
-        let s =
-            "####.###...##..###..####.###...##....##.\n\
-             #....#..#.#..#.#..#.#....#..#.#..#....#.\n\
-             ###..#..#.#....#..#.###..#..#.#.......#.\n\
-             #....###..#....###..#....###..#.......#.\n\
-             #....#.#..#..#.#.#..#....#....#..#.#..#.\n\
-             ####.#..#..##..#..#.####.#.....##...##..\n".to_string();
-        assert_eq!(d.part1(), Answer::Number(11720));
-        assert_eq!(d.part2(), Answer::Message(s));
-    }
+        let mut pending: BTreeMap<usize, Result<DayResult, AocError>> = BTreeMap::new();
+        let mut next = 0;
+        for (i, result) in receiver {
+            pending.insert(i, result);
+            while let Some(result) = pending.remove(&next) {
+                match result {
+                    Ok(result) => {
+                        for record in &result.records {
+                            println!("{}", record.format(options.format));
+                            verdicts.extend(record.verdict);
+                        }
+                        timings.push(result.timing);
+                    }
+                    Err(e) => {
+                        eprintln!("day {}: {}", inputs[next].0.number, e);
+                        failed = true;
+                    }
+                }
+                next += 1;
+            }
+        }
+    });
 
-    #[test]
-    fn test_day11() {
-        let d = Day11::load("data_aoc2022/day11_input.txt");
-        assert_eq!(d.part1(), Answer::Number(62491));
-        assert_eq!(d.part2(), Answer::Number(17408399184));
+    if options.verify {
+        let count = |v: Verdict| verdicts.iter().filter(|&&verdict| verdict == v).count();
+        let summary = format!("verify ({}): {} passed, {} failed, {} unknown",
+            options.input_set(), count(Verdict::Pass), count(Verdict::Fail), count(Verdict::Unknown));
+        if options.format == Format::Text {
+            println!();
+            println!("{}", summary);
+        }
+        else {
+            eprintln!("{}", summary);
+        }
+        if count(Verdict::Fail) > 0 {
+            failed = true;
+        }
     }
 
-    #[test]
-    fn test_day12() {
-        let d = Day12::load("data_aoc2022/day12_input.txt");
-        assert_eq!(d.part1(), Answer::Number(534));
-        assert_eq!(d.part2(), Answer::Number(525));
+    if let Some(filename) = &options.trace {
+        if let Err(e) = observer::finish_trace() {
+            eprintln!("{}: {}", filename, e);
+            failed = true;
+        }
     }
 
-    #[test]
-    fn test_day13() {
-        let d = Day13::load("data_aoc2022/day13_input.txt");
-        assert_eq!(d.part1(), Answer::Number(6235));
-        assert_eq!(d.part2(), Answer::Number(22866));
+    // Keep the timing table out of machine readable output.
+    if options.time {
+        if options.format == Format::Text {
+            println!();
+            print!("{}", timing::format_table(&timings));
+        }
+        else {
+            eprint!("{}", timing::format_table(&timings));
+        }
     }
-
-    #[test]
-    fn test_day14() {
-        let d = Day14::load("data_aoc2022/day14_input.txt");
-        assert_eq!(d.part1(), Answer::Number(757));
-        assert_eq!(d.part2(), Answer::Number(24943));
+    if let Some(filename) = &options.report {
+        let report = if filename.ends_with(".json") {
+            timing::format_json(&timings)
+        }
+        else {
+            timing::format_csv(&timings)
+        };
+        if let Err(e) = fs::write(filename, report) {
+            eprintln!("{}: {}", filename, e);
+            failed = true;
+        }
     }
 
-    #[test]
-    fn test_day15() {
-        let d = Day15::load("data_aoc2022/day15_input.txt");
-        assert_eq!(d.part1(), Answer::Number(5127797));
-        assert_eq!(d.part2(), Answer::Number(12518502636475));
-    }
-    
-    #[test]
-    fn test_day16() {
-        let d = Day16::load("data_aoc2022/day16_input.txt");
-        assert_eq!(d.part1(), Answer::Number(1641));
-        assert_eq!(d.part2(), Answer::Number(2261));
+    if failed {
+        process::exit(1);
     }
+}
 
-    #[test]
-    fn test_day17() {
-        let d = Day17::load("data_aoc2022/day17_input.txt");
-        assert_eq!(d.part1(), Answer::Number(3069));
-        assert_eq!(d.part2(), Answer::Number(1523167155404_usize));
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
+    // Check every day against the expected answers for the input set in
+    // $AOC_DATA (default data_aoc2022).  Inputs differ for each participant
+    // and aren't checked in, so days whose input is missing are skipped.
     #[test]
-    fn test_day18() {
-        let d = Day18::load("data_aoc2022/day18_input.txt");
-        assert_eq!(d.part1(), Answer::Number(4450));
-        assert_eq!(d.part2(), Answer::Number(2564));
+    fn test_answers() {
+        let data_dir = env::var("AOC_DATA").unwrap_or("data_aoc2022".to_string());
+        let options = Options::parse(&["--data".to_string(), data_dir.clone()]).unwrap();
+        let answers = Answers::load("answers.toml").unwrap();
+        let set = options.input_set();
+
+        for info in registry::DAYS {
+            let input = match Input::open(&options.input_path(info.number)) {
+                Ok(input) => input,
+                Err(_) => {
+                    println!("day {}: no input in {}, skipped", info.number, data_dir);
+                    continue;
+                }
+            };
+            let day = (info.parse)(&input).unwrap();
+            for (part, answer) in [(1, day.part1()), (2, day.part2())] {
+                let answer = ocr::read_letters(answer);
+                let verdict = answers.verify(&set, info.number, part, &answer);
+                assert_ne!(verdict, Verdict::Fail, "day {} part {}: got {:?}, expected {:?}",
+                    info.number, part, answer, answers.expected(&set, info.number, part));
+            }
+        }
     }
 }
-
@@ -0,0 +1,211 @@
+// Watching searches as they run.
+//
+// Each search engine reports what it does to a Monitor, which keeps Stats.
+// When a trace file has been set (the runner's --trace), every search also
+// writes its progress there, so a search that blows up can be profiled
+// without touching the day that runs it.
+//
+// Trace files are tab separated, one line per expanded state, new best and
+// finished search, each with the counters at that moment:
+//
+//   search  engine  elapsed_us  event  score  expanded  generated  pruned  queue
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use lazy_static::lazy_static;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Event {
+    Generated,          // a state was pushed onto the queue
+    Expanded,           // a state's successors were generated
+    Pruned(usize),      // this many states were discarded by their bound
+    Improved(isize),    // a better solution, with its score (or cost)
+    Finished,
+}
+
+// A new best solution, and when it was found.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Improvement {
+    pub score: isize,
+    pub expanded: usize,
+    pub elapsed: Duration,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Stats {
+    pub generated: usize,
+    pub expanded: usize,
+    pub pruned: usize,
+    pub max_queue: usize,               // high-water mark of the queue
+    pub improvements: Vec<Improvement>, // best so far, over time
+}
+
+pub struct Monitor {
+    id: usize,
+    engine: &'static str,
+    started: Instant,
+    queue: usize,
+    stats: Stats,
+}
+
+lazy_static! {
+    static ref TRACE: Mutex<Option<Trace<BufWriter<File>>>> = Mutex::new(None);
+}
+static TRACING: AtomicBool = AtomicBool::new(false);
+static NEXT_ID: AtomicUsize = AtomicUsize::new(1);
+
+impl Monitor {
+    pub fn new(engine: &'static str) -> Monitor {
+        Monitor {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            engine,
+            started: Instant::now(),
+            queue: 0,
+            stats: Stats::default(),
+        }
+    }
+
+    // Distinguishes searches in a trace.
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    pub fn engine(&self) -> &'static str {
+        self.engine
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    // Queue length as of the latest event.
+    pub fn queue(&self) -> usize {
+        self.queue
+    }
+
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    // Record an event, given the length of the queue afterwards.
+    pub fn record(&mut self, event: Event, queue: usize) {
+        self.queue = queue;
+        self.stats.max_queue = self.stats.max_queue.max(queue);
+        match event {
+            Event::Generated => self.stats.generated += 1,
+            Event::Expanded => self.stats.expanded += 1,
+            Event::Pruned(count) => self.stats.pruned += count,
+            Event::Improved(score) => {
+                let improvement = Improvement { score, expanded: self.stats.expanded, elapsed: self.elapsed() };
+                self.stats.improvements.push(improvement);
+            }
+            Event::Finished => {}
+        }
+
+        if TRACING.load(Ordering::Relaxed) {
+            if let Some(trace) = TRACE.lock().unwrap().as_mut() {
+                trace.notify(&event, self);
+            }
+        }
+    }
+}
+
+// Writes a search's progress as tab separated lines.
+pub struct Trace<W: Write> {
+    out: W,
+}
+
+impl<W: Write> Trace<W> {
+    pub fn new(mut out: W) -> Trace<W> {
+        // Tracing is for diagnosis, so a failed write is ignored rather than
+        // failing the search.
+        let _ = writeln!(out, "search\tengine\telapsed_us\tevent\tscore\texpanded\tgenerated\tpruned\tqueue");
+        Trace { out }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+
+    pub fn notify(&mut self, event: &Event, monitor: &Monitor) {
+        let (name, score) = match event {
+            Event::Expanded => ("expanded", String::new()),
+            Event::Improved(score) => ("improved", score.to_string()),
+            Event::Finished => ("finished", String::new()),
+            _ => return,
+        };
+        let stats = monitor.stats();
+        let _ = writeln!(self.out, "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            monitor.id(), monitor.engine(), monitor.elapsed().as_micros(), name, score,
+            stats.expanded, stats.generated, stats.pruned, monitor.queue());
+    }
+}
+
+// Trace every search from now on to the given file.
+pub fn trace_to(filename: &str) -> io::Result<()> {
+    let file = File::create(filename)?;
+    *TRACE.lock().unwrap() = Some(Trace::new(BufWriter::new(file)));
+    TRACING.store(true, Ordering::Relaxed);
+
+    Ok(())
+}
+
+// Stop tracing, flushing the trace file.
+pub fn finish_trace() -> io::Result<()> {
+    TRACING.store(false, Ordering::Relaxed);
+    match TRACE.lock().unwrap().take() {
+        Some(trace) => trace.into_inner().flush(),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats() {
+        let mut monitor = Monitor::new("test");
+
+        monitor.record(Event::Generated, 1);
+        monitor.record(Event::Expanded, 0);
+        monitor.record(Event::Generated, 1);
+        monitor.record(Event::Generated, 2);
+        monitor.record(Event::Pruned(2), 0);
+        monitor.record(Event::Improved(7), 0);
+        monitor.record(Event::Finished, 0);
+
+        let stats = monitor.stats();
+        assert_eq!(stats.generated, 3);
+        assert_eq!(stats.expanded, 1);
+        assert_eq!(stats.pruned, 2);
+        assert_eq!(stats.max_queue, 2);
+        assert_eq!(stats.improvements.len(), 1);
+        assert_eq!(stats.improvements[0].score, 7);
+        assert_eq!(stats.improvements[0].expanded, 1);
+    }
+
+    #[test]
+    fn test_trace() {
+        let mut monitor = Monitor::new("test");
+        let mut trace = Trace::new(Vec::new());
+        monitor.record(Event::Generated, 1);
+        trace.notify(&Event::Generated, &monitor);
+        monitor.record(Event::Expanded, 3);
+        trace.notify(&Event::Expanded, &monitor);
+        monitor.record(Event::Improved(42), 3);
+        trace.notify(&Event::Improved(42), &monitor);
+
+        let text = String::from_utf8(trace.into_inner()).unwrap();
+        let lines: Vec<Vec<&str>> = text.lines().map(|l| l.split('\t').collect()).collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0][0], "search");
+        assert_eq!(lines[1][1], "test");
+        assert_eq!(lines[1][3], "expanded");
+        assert_eq!(lines[1][5..], ["1", "1", "0", "3"]);
+        assert_eq!(lines[2][3..5], ["improved", "42"]);
+    }
+}
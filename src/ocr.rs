@@ -0,0 +1,138 @@
+// Reading the letters some puzzles draw instead of giving a number.
+//
+// AoC uses a font of 4x6 glyphs with a blank column between letters, so a
+// screen 40 pixels wide holds 8 of them.  Only the letters that have been
+// seen in puzzles are known.
+
+use crate::day::Answer;
+
+const GLYPH_WIDTH: usize = 4;
+const GLYPH_HEIGHT: usize = 6;
+const GLYPH_SPACING: usize = 5;
+
+// Each glyph's rows, top to bottom, run together.
+const GLYPHS: [(char, &str); 18] = [
+    ('A', ".##.#..##..######..##..#"),
+    ('B', "###.#..####.#..##..####."),
+    ('C', ".##.#..##...#...#..#.##."),
+    ('E', "#####...###.#...#...####"),
+    ('F', "#####...###.#...#...#..."),
+    ('G', ".##.#..##...#.###..#.###"),
+    ('H', "#..##..######..##..##..#"),
+    ('I', ".###..#...#...#...#..###"),
+    ('J', "..##...#...#...##..#.##."),
+    ('K', "#..##.#.##..#.#.#.#.#..#"),
+    ('L', "#...#...#...#...#...####"),
+    ('O', ".##.#..##..##..##..#.##."),
+    ('P', "###.#..##..####.#...#..."),
+    ('R', "###.#..##..####.#.#.#..#"),
+    ('S', ".####...#....##....####."),
+    ('U', "#..##..##..##..##..#.##."),
+    ('Y', "#...#....#.#..#...#...#."),
+    ('Z', "####...#..#..#..#...####"),
+];
+
+// Decode a row-major image of lit cells into letters.  Returns None unless
+// the image is exactly one line of text and every glyph is recognized.
+pub fn decode(width: usize, height: usize, cells: &[bool]) -> Option<String> {
+    if height != GLYPH_HEIGHT || width < GLYPH_WIDTH || cells.len() != width * height {
+        return None;
+    }
+
+    let mut text = String::new();
+    let mut left = 0;
+    while left + GLYPH_WIDTH <= width {
+        let mut pattern = String::new();
+        for y in 0..GLYPH_HEIGHT {
+            for x in left..left + GLYPH_WIDTH {
+                pattern.push(if cells[y*width + x] { '#' } else { '.' });
+            }
+        }
+        let (letter, _) = GLYPHS.iter().find(|(_, glyph)| *glyph == pattern)?;
+        text.push(*letter);
+        left += GLYPH_SPACING;
+    }
+
+    Some(text)
+}
+
+// Decode an image drawn as lines of '#' and '.'.
+pub fn decode_text(image: &str) -> Option<String> {
+    let rows: Vec<&str> = image.lines().collect();
+    let width = rows.first()?.len();
+    let mut cells: Vec<bool> = Vec::new();
+    for row in &rows {
+        if row.len() != width || !row.chars().all(|c| c == '#' || c == '.') {
+            return None;
+        }
+        cells.extend(row.chars().map(|c| c == '#'));
+    }
+
+    decode(width, rows.len(), &cells)
+}
+
+// The runner's post-processing step: an answer that spells something out is
+// replaced by the text, anything else is returned unchanged.
+pub fn read_letters(answer: Answer) -> Answer {
+    let text = match &answer {
+        Answer::Grid { width, height, cells } => decode(*width, *height, cells),
+        Answer::Message(image) => decode_text(image),
+        _ => None,
+    };
+
+    match text {
+        Some(text) => Answer::Message(text),
+        None => answer,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Every known glyph, in order, with a blank column after each.
+    fn alphabet() -> String {
+        let mut image = String::new();
+        for y in 0..GLYPH_HEIGHT {
+            for (_, glyph) in GLYPHS {
+                image.push_str(&glyph[y*GLYPH_WIDTH..(y + 1)*GLYPH_WIDTH]);
+                image.push('.');
+            }
+            image.push('\n');
+        }
+        image
+    }
+
+    #[test]
+    fn test_decode_text() {
+        assert_eq!(decode_text(&alphabet()), Some("ABCEFGHIJKLOPRSUYZ".to_string()));
+
+        let image =
+            "####.###...##..###..####.###...##....##.\n\
+             #....#..#.#..#.#..#.#....#..#.#..#....#.\n\
+             ###..#..#.#....#..#.###..#..#.#.......#.\n\
+             #....###..#....###..#....###..#.......#.\n\
+             #....#.#..#..#.#.#..#....#....#..#.#..#.\n\
+             ####.#..#..##..#..#.####.#.....##...##..\n";
+        assert_eq!(decode_text(image), Some("ERCREPCJ".to_string()));
+    }
+
+    #[test]
+    fn test_unrecognized() {
+        assert_eq!(decode_text(""), None);
+        assert_eq!(decode_text("####\n#..#\n"), None);
+        assert_eq!(decode_text("##..##..##..##..##..##..##..##..##..##..\n".repeat(6).as_str()), None);
+        assert_eq!(decode_text("SHMSDGZVC"), None);
+    }
+
+    #[test]
+    fn test_read_letters() {
+        let cells: Vec<bool> = alphabet().chars().filter(|c| *c != '\n').map(|c| c == '#').collect();
+        let grid = Answer::Grid { width: GLYPHS.len() * GLYPH_SPACING, height: GLYPH_HEIGHT, cells };
+        assert_eq!(read_letters(grid), Answer::Message("ABCEFGHIJKLOPRSUYZ".to_string()));
+
+        assert_eq!(read_letters(Answer::Number(3)), Answer::Number(3));
+        let message = Answer::Message("SHMSDGZVC".to_string());
+        assert_eq!(read_letters(message), Answer::Message("SHMSDGZVC".to_string()));
+    }
+}
@@ -0,0 +1,211 @@
+// Formatting of answers for the runner.
+//
+// Text is the traditional human readable output.  Json writes one object per
+// line and Tsv one tab separated row per line (after a header), so runs over
+// different inputs can be diffed line by line.  Only text shows an answer's
+// detail, after the answer.
+
+use crate::answers::Verdict;
+use crate::day::Answer;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Text,
+    Json,
+    Tsv,
+}
+
+impl Format {
+    pub fn parse(s: &str) -> Result<Format, String> {
+        match s.trim() {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "tsv" => Ok(Format::Tsv),
+            _ => Err(format!("Invalid format '{}', expected json, tsv or text", s)),
+        }
+    }
+
+    // Line printed before any records, if the format has one.
+    pub fn header(&self) -> Option<&'static str> {
+        match self {
+            Format::Tsv => Some("day\tpart\tkind\tvalue\ttime_ms\tverdict"),
+            _ => None,
+        }
+    }
+}
+
+// One part's answer, with the (median) time taken to compute it and, when
+// verifying, how it compared with the expected answer.
+pub struct Record {
+    pub day: usize,
+    pub part: usize,
+    pub answer: Answer,
+    pub time: Duration,
+    pub verdict: Option<Verdict>,
+}
+
+impl Record {
+    pub fn format(&self, format: Format) -> String {
+        match format {
+            Format::Text => {
+                let verdict = match self.verdict {
+                    Some(verdict) => format!(" [{}]", verdict),
+                    None => String::new(),
+                };
+                let mut text = if self.answer.is_multiline() {
+                    format!("day {}, part {}:{} \n{}", self.day, self.part, verdict, self.answer)
+                }
+                else {
+                    format!("day {}, part {}: {}{}", self.day, self.part, self.answer, verdict)
+                };
+                if let Some(detail) = self.answer.detail() {
+                    if !text.ends_with('\n') {
+                        text.push('\n');
+                    }
+                    text.push_str(detail);
+                }
+                text
+            }
+            Format::Json => {
+                let value = match self.answer.value() {
+                    Answer::None => "null".to_string(),
+                    Answer::Number(_) | Answer::Signed(_) | Answer::Big(_) => self.answer.to_string(),
+                    _ => json_string(&self.answer.to_string()),
+                };
+                let verdict = match self.verdict {
+                    Some(verdict) => format!("\"{}\"", verdict),
+                    None => "null".to_string(),
+                };
+                format!("{{\"day\": {}, \"part\": {}, \"kind\": \"{}\", \"value\": {}, \"time_ms\": {:.6}, \"verdict\": {}}}",
+                    self.day, self.part, self.kind(), value, self.time_ms(), verdict)
+            }
+            Format::Tsv => {
+                let value = match self.answer.value() {
+                    Answer::None => String::new(),
+                    _ => tsv_escape(&self.answer.to_string()),
+                };
+                let verdict = match self.verdict {
+                    Some(verdict) => verdict.to_string(),
+                    None => String::new(),
+                };
+                format!("{}\t{}\t{}\t{}\t{:.6}\t{}", self.day, self.part, self.kind(), value, self.time_ms(), verdict)
+            }
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self.answer.value() {
+            Answer::None => "none",
+            Answer::Number(_) => "number",
+            Answer::Signed(_) => "signed",
+            Answer::Big(_) => "big",
+            Answer::Message(_) => "message",
+            Answer::Grid { .. } => "grid",
+            Answer::Detailed { answer, .. } => unreachable!("{:?} has no value", answer),
+        }
+    }
+
+    fn time_ms(&self) -> f64 {
+        self.time.as_secs_f64() * 1000.0
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+
+    out
+}
+
+// Keep a value on one line of one column.
+fn tsv_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('\t', "\\t").replace('\n', "\\n").replace('\r', "\\r")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(answer: Answer) -> Record {
+        Record { day: 10, part: 2, answer, time: Duration::from_micros(1500), verdict: None }
+    }
+
+    #[test]
+    fn test_format_parse() {
+        assert_eq!(Format::parse("json"), Ok(Format::Json));
+        assert_eq!(Format::parse("tsv"), Ok(Format::Tsv));
+        assert_eq!(Format::parse("text"), Ok(Format::Text));
+        assert!(Format::parse("xml").is_err());
+    }
+
+    #[test]
+    fn test_text() {
+        assert_eq!(record(Answer::None).format(Format::Text), "day 10, part 2: No Answer");
+        assert_eq!(record(Answer::Number(42)).format(Format::Text), "day 10, part 2: 42");
+        assert_eq!(record(Answer::Message("#.\n.#\n".to_string())).format(Format::Text), "day 10, part 2: \n#.\n.#\n");
+        assert_eq!(record(Answer::Signed(-3)).format(Format::Text), "day 10, part 2: -3");
+        let grid = Answer::Grid { width: 2, height: 2, cells: vec![true, false, false, true] };
+        assert_eq!(record(grid).format(Format::Text), "day 10, part 2: \n#.\n.#\n");
+    }
+
+    #[test]
+    fn test_detail() {
+        let detailed = Answer::Detailed { answer: Box::new(Answer::Number(42)), detail: "6 x 7".to_string() };
+        assert_eq!(record(detailed).format(Format::Text), "day 10, part 2: 42\n6 x 7");
+        let detailed = Answer::Detailed { answer: Box::new(Answer::Number(42)), detail: "6 x 7".to_string() };
+        assert_eq!(record(detailed).format(Format::Json),
+            "{\"day\": 10, \"part\": 2, \"kind\": \"number\", \"value\": 42, \"time_ms\": 1.500000, \"verdict\": null}");
+        let message = Answer::Message("#.\n".to_string());
+        let detailed = Answer::Detailed { answer: Box::new(message), detail: "one".to_string() };
+        assert_eq!(record(detailed).format(Format::Tsv), "10\t2\tmessage\t#.\\n\t1.500000\t");
+        let message = Answer::Message("#.\n".to_string());
+        let detailed = Answer::Detailed { answer: Box::new(message), detail: "one".to_string() };
+        assert_eq!(record(detailed).format(Format::Text), "day 10, part 2: \n#.\none");
+    }
+
+    #[test]
+    fn test_json() {
+        assert_eq!(record(Answer::Number(42)).format(Format::Json),
+            "{\"day\": 10, \"part\": 2, \"kind\": \"number\", \"value\": 42, \"time_ms\": 1.500000, \"verdict\": null}");
+        assert_eq!(record(Answer::None).format(Format::Json),
+            "{\"day\": 10, \"part\": 2, \"kind\": \"none\", \"value\": null, \"time_ms\": 1.500000, \"verdict\": null}");
+        assert_eq!(record(Answer::Message("a\"b\n\u{1}".to_string())).format(Format::Json),
+            "{\"day\": 10, \"part\": 2, \"kind\": \"message\", \"value\": \"a\\\"b\\n\\u0001\", \"time_ms\": 1.500000, \"verdict\": null}");
+    }
+
+    #[test]
+    fn test_tsv() {
+        assert_eq!(record(Answer::Message("#.\n.#\n".to_string())).format(Format::Tsv),
+            "10\t2\tmessage\t#.\\n.#\\n\t1.500000\t");
+        assert_eq!(record(Answer::None).format(Format::Tsv), "10\t2\tnone\t\t1.500000\t");
+        let grid = Answer::Grid { width: 2, height: 1, cells: vec![true, false] };
+        assert_eq!(record(grid).format(Format::Tsv), "10\t2\tgrid\t#.\\n\t1.500000\t");
+        assert_eq!(record(Answer::Big(-(1 << 70))).format(Format::Tsv), "10\t2\tbig\t-1180591620717411303424\t1.500000\t");
+        assert_eq!(Format::Tsv.header().unwrap().split('\t').count(), 6);
+    }
+
+    #[test]
+    fn test_verdict() {
+        let mut r = record(Answer::Number(42));
+        r.verdict = Some(Verdict::Fail);
+        assert_eq!(r.format(Format::Text), "day 10, part 2: 42 [fail]");
+        assert!(r.format(Format::Json).ends_with("\"verdict\": \"fail\"}"));
+        assert!(r.format(Format::Tsv).ends_with("\tfail"));
+
+        let mut r = record(Answer::Message("#.\n".to_string()));
+        r.verdict = Some(Verdict::Pass);
+        assert_eq!(r.format(Format::Text), "day 10, part 2: [pass] \n#.\n");
+    }
+}
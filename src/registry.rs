@@ -0,0 +1,99 @@
+// The table of implemented days.  The runner, the tests and any other tooling
+// enumerate DAYS rather than naming each day individually, so adding a day is
+// a matter of adding its module to main.rs and one entry here.
+
+use crate::day::Day;
+use crate::error::AocError;
+use crate::input::Input;
+use crate::day1::Day1;
+use crate::day2::Day2;
+use crate::day3::Day3;
+use crate::day4::Day4;
+use crate::day5::Day5;
+use crate::day6::Day6;
+use crate::day7::Day7;
+use crate::day8::Day8;
+use crate::day9::Day9;
+use crate::day10::Day10;
+use crate::day11::Day11;
+use crate::day12::Day12;
+use crate::day13::Day13;
+use crate::day14::Day14;
+use crate::day15::Day15;
+use crate::day16::Day16;
+use crate::day17::Day17;
+use crate::day18::Day18;
+
+pub struct DayInfo {
+    pub number: usize,
+    pub title: &'static str,
+    pub parse: fn(&Input) -> Result<Box<dyn Day>, AocError>,
+    pub variants: &'static [&'static str],  // ways of solving it, the default first
+}
+
+// Generic constructor so every entry can share the same function pointer type.
+fn parse<D: Day + 'static>(input: &Input) -> Result<Box<dyn Day>, AocError> {
+    Ok(Box::new(D::parse(input)?))
+}
+
+pub const DAYS: &[DayInfo] = &[
+    DayInfo { number: 1, title: "Calorie Counting", parse: parse::<Day1>, variants: &[] },
+    DayInfo { number: 2, title: "Rock Paper Scissors", parse: parse::<Day2>, variants: &[] },
+    DayInfo { number: 3, title: "Rucksack Reorganization", parse: parse::<Day3>, variants: &[] },
+    DayInfo { number: 4, title: "Camp Cleanup", parse: parse::<Day4>, variants: &[] },
+    DayInfo { number: 5, title: "Supply Stacks", parse: parse::<Day5>, variants: &[] },
+    DayInfo { number: 6, title: "Tuning Trouble", parse: parse::<Day6>, variants: &[] },
+    DayInfo { number: 7, title: "No Space Left On Device", parse: parse::<Day7>, variants: &[] },
+    DayInfo { number: 8, title: "Treetop Tree House", parse: parse::<Day8>, variants: &[] },
+    DayInfo { number: 9, title: "Rope Bridge", parse: parse::<Day9>, variants: &[] },
+    DayInfo { number: 10, title: "Cathode-Ray Tube", parse: parse::<Day10>, variants: &[] },
+    DayInfo { number: 11, title: "Monkey in the Middle", parse: parse::<Day11>, variants: &[] },
+    DayInfo { number: 12, title: "Hill Climbing Algorithm", parse: parse::<Day12>, variants: &[] },
+    DayInfo { number: 13, title: "Distress Signal", parse: parse::<Day13>, variants: &[] },
+    DayInfo { number: 14, title: "Regolith Reservoir", parse: parse::<Day14>, variants: &[] },
+    DayInfo { number: 15, title: "Beacon Exclusion Zone", parse: parse::<Day15>,
+              variants: &["perimeter", "lines", "all"] },
+    DayInfo { number: 16, title: "Proboscidea Volcanium", parse: parse::<Day16>,
+              variants: &["branch_bound", "dp", "greedy_bound", "distance_bound"] },
+    DayInfo { number: 17, title: "Pyroclastic Flow", parse: parse::<Day17>, variants: &[] },
+    DayInfo { number: 18, title: "Boiling Boulders", parse: parse::<Day18>, variants: &[] },
+];
+
+// Look up the registry entry for day n.
+pub fn find(n: usize) -> Option<&'static DayInfo> {
+    DAYS.iter().find(|info| info.number == n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_numbering() {
+        for (i, info) in DAYS.iter().enumerate() {
+            assert_eq!(info.number, i + 1);
+            assert!(!info.title.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_find() {
+        assert_eq!(find(16).unwrap().title, "Proboscidea Volcanium");
+        assert!(find(0).is_none());
+        assert!(find(DAYS.len() + 1).is_none());
+        assert_eq!(find(15).unwrap().variants[0], "perimeter");
+        assert_eq!(find(16).unwrap().variants, ["branch_bound", "dp", "greedy_bound", "distance_bound"]);
+        assert!(find(1).unwrap().variants.is_empty());
+    }
+
+    #[test]
+    fn test_parse_examples() {
+        // Every registered day can be constructed from its first example,
+        // and the examples contain nothing a strict parse would reject.
+        for info in DAYS {
+            let input = Input::open(&format!("examples/day{}_example1.txt", info.number)).unwrap();
+            let parsed = (info.parse)(&input.strict(true));
+            assert!(parsed.is_ok(), "day {}: {}", info.number, parsed.err().unwrap());
+        }
+    }
+}
@@ -0,0 +1,43 @@
+// Helpers shared by the tests.
+
+// A small LCG, so randomised tests are repeatable without extra crates.
+pub fn rng(seed: u64) -> impl FnMut() -> u64 {
+    let mut state = seed;
+    move || {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        state >> 33
+    }
+}
+
+// Run case count times, drawing from one generator seeded with seed: next(n)
+// is a number below n.  Each case builds a random puzzle and checks the code
+// under test against a simpler, slower version.
+pub fn random_cases<F>(seed: u64, count: usize, mut case: F)
+    where F: FnMut(&mut dyn FnMut(usize) -> usize)
+{
+    let mut rng = rng(seed);
+    let mut next = |n: usize| (rng() % n as u64) as usize;
+    for _ in 0..count {
+        case(&mut next);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rng() {
+        let (mut a, mut b) = (rng(7), rng(7));
+        let drawn: Vec<u64> = (0..100).map(|_| a()).collect();
+        assert!(drawn.iter().all(|n| *n == b() && *n < 1 << 31));
+        assert!(drawn.windows(2).any(|pair| pair[0] != pair[1]));
+
+        let mut cases = 0;
+        random_cases(7, 20, |next| {
+            assert!(next(3) < 3);
+            cases += 1;
+        });
+        assert_eq!(cases, 20);
+    }
+}
@@ -0,0 +1,209 @@
+use std::time::{Duration, Instant};
+
+// Run f, returning its result and how long it took.
+pub fn time<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let result = f();
+
+    (result, start.elapsed())
+}
+
+// Run f the given number of times (at least once), returning the last result
+// and every run's duration.
+pub fn repeat<T>(runs: usize, mut f: impl FnMut() -> T) -> (T, Samples) {
+    let mut durations: Vec<Duration> = Vec::new();
+    let (mut result, elapsed) = time(&mut f);
+    durations.push(elapsed);
+
+    for _ in 1..runs {
+        let (r, elapsed) = time(&mut f);
+        result = r;
+        durations.push(elapsed);
+    }
+
+    (result, Samples::new(durations))
+}
+
+// The durations measured for one stage of one day, kept sorted.
+#[derive(Clone, Debug)]
+pub struct Samples {
+    durations: Vec<Duration>,
+}
+
+impl Samples {
+    pub fn new(mut durations: Vec<Duration>) -> Samples {
+        assert!(!durations.is_empty());
+        durations.sort();
+
+        Samples { durations }
+    }
+
+    pub fn runs(&self) -> usize {
+        self.durations.len()
+    }
+
+    pub fn min(&self) -> Duration {
+        self.durations[0]
+    }
+
+    pub fn max(&self) -> Duration {
+        self.durations[self.durations.len() - 1]
+    }
+
+    pub fn median(&self) -> Duration {
+        let n = self.durations.len();
+        if n % 2 == 1 {
+            self.durations[n / 2]
+        }
+        else {
+            (self.durations[n / 2 - 1] + self.durations[n / 2]) / 2
+        }
+    }
+}
+
+// Timings for one day.  A part that wasn't run has no samples.
+pub struct DayTiming {
+    pub day: usize,
+    pub parse: Samples,
+    pub part1: Option<Samples>,
+    pub part2: Option<Samples>,
+}
+
+impl DayTiming {
+    // (stage name, samples) for every stage that ran.
+    fn stages(&self) -> Vec<(&'static str, &Samples)> {
+        let mut stages = vec![("parse", &self.parse)];
+        if let Some(s) = &self.part1 {
+            stages.push(("part1", s));
+        }
+        if let Some(s) = &self.part2 {
+            stages.push(("part2", s));
+        }
+
+        stages
+    }
+}
+
+fn ms(d: Duration) -> f64 {
+    d.as_secs_f64() * 1000.0
+}
+
+// A human readable table, one row per stage, with per-day and overall totals.
+// Totals are sums of the per-stage statistics.
+pub fn format_table(timings: &[DayTiming]) -> String {
+    let runs = timings.first().map(|t| t.parse.runs()).unwrap_or(1);
+    let mut s = String::new();
+
+    let row = |s: &mut String, day: &str, stage: &str, min: Duration, median: Duration, max: Duration| {
+        if runs > 1 {
+            s.push_str(&format!("{:>5}  {:<6} {:>12.3} {:>12.3} {:>12.3}\n", day, stage, ms(min), ms(median), ms(max)));
+        }
+        else {
+            s.push_str(&format!("{:>5}  {:<6} {:>12.3}\n", day, stage, ms(median)));
+        }
+    };
+
+    if runs > 1 {
+        s.push_str(&format!("{:>5}  {:<6} {:>12} {:>12} {:>12}   ({} runs)\n", "day", "stage", "min ms", "median ms", "max ms", runs));
+    }
+    else {
+        s.push_str(&format!("{:>5}  {:<6} {:>12}\n", "day", "stage", "ms"));
+    }
+
+    let mut totals = (Duration::ZERO, Duration::ZERO, Duration::ZERO);
+    for t in timings {
+        let mut day_totals = (Duration::ZERO, Duration::ZERO, Duration::ZERO);
+        for (stage, samples) in t.stages() {
+            row(&mut s, &t.day.to_string(), stage, samples.min(), samples.median(), samples.max());
+            day_totals.0 += samples.min();
+            day_totals.1 += samples.median();
+            day_totals.2 += samples.max();
+        }
+        row(&mut s, "", "total", day_totals.0, day_totals.1, day_totals.2);
+        totals.0 += day_totals.0;
+        totals.1 += day_totals.1;
+        totals.2 += day_totals.2;
+    }
+    row(&mut s, "all", "total", totals.0, totals.1, totals.2);
+
+    s
+}
+
+pub fn format_csv(timings: &[DayTiming]) -> String {
+    let mut s = String::from("day,stage,runs,min_ms,median_ms,max_ms\n");
+    for t in timings {
+        for (stage, samples) in t.stages() {
+            s.push_str(&format!("{},{},{},{:.6},{:.6},{:.6}\n",
+                t.day, stage, samples.runs(), ms(samples.min()), ms(samples.median()), ms(samples.max())));
+        }
+    }
+
+    s
+}
+
+pub fn format_json(timings: &[DayTiming]) -> String {
+    let mut records: Vec<String> = Vec::new();
+    for t in timings {
+        for (stage, samples) in t.stages() {
+            records.push(format!(
+                "  {{\"day\": {}, \"stage\": \"{}\", \"runs\": {}, \"min_ms\": {:.6}, \"median_ms\": {:.6}, \"max_ms\": {:.6}}}",
+                t.day, stage, samples.runs(), ms(samples.min()), ms(samples.median()), ms(samples.max())));
+        }
+    }
+
+    format!("[\n{}\n]\n", records.join(",\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn millis(v: &[u64]) -> Samples {
+        Samples::new(v.iter().map(|n| Duration::from_millis(*n)).collect())
+    }
+
+    #[test]
+    fn test_samples() {
+        let s = millis(&[5, 1, 3]);
+        assert_eq!(s.runs(), 3);
+        assert_eq!(s.min(), Duration::from_millis(1));
+        assert_eq!(s.median(), Duration::from_millis(3));
+        assert_eq!(s.max(), Duration::from_millis(5));
+
+        let s = millis(&[4, 1, 2, 10]);
+        assert_eq!(s.median(), Duration::from_millis(3));
+    }
+
+    #[test]
+    fn test_repeat() {
+        let mut count = 0;
+        let (result, samples) = repeat(4, || { count += 1; count });
+        assert_eq!(result, 4);
+        assert_eq!(samples.runs(), 4);
+
+        let (_result, samples) = repeat(0, || 1);
+        assert_eq!(samples.runs(), 1);
+    }
+
+    #[test]
+    fn test_formats() {
+        let timings = vec![
+            DayTiming { day: 3, parse: millis(&[1]), part1: Some(millis(&[2])), part2: None },
+        ];
+
+        assert_eq!(format_csv(&timings),
+            "day,stage,runs,min_ms,median_ms,max_ms\n\
+             3,parse,1,1.000000,1.000000,1.000000\n\
+             3,part1,1,2.000000,2.000000,2.000000\n");
+
+        let json = format_json(&timings);
+        assert!(json.starts_with("[\n  {\"day\": 3, \"stage\": \"parse\", \"runs\": 1,"));
+        assert!(json.ends_with("\"max_ms\": 2.000000}\n]\n"));
+
+        let table = format_table(&timings);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert!(lines[3].contains("total") && lines[3].ends_with("3.000"));
+        assert!(lines[4].starts_with("  all"));
+    }
+}
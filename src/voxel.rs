@@ -0,0 +1,267 @@
+use std::collections::{HashSet, VecDeque};
+
+// Sets of unit cubes on a 3D integer grid.
+//
+// Air and water move between cubes that share a face, so that is what the
+// exterior and cavities are built on, but components can be found using any
+// of the usual connectivities.
+
+pub type Voxel = (isize, isize, isize);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Connectivity {
+    Faces,      // the 6 cubes sharing a face
+    Edges,      // the 18 cubes sharing a face or an edge
+    Corners,    // all 26 cubes touching at all
+}
+
+// Offsets to every neighbour, faces first, then edges, then corners, so
+// that each connectivity's neighbours are a prefix.
+const OFFSETS: [Voxel; 26] = [
+    (-1, 0, 0), (1, 0, 0), (0, -1, 0), (0, 1, 0), (0, 0, -1), (0, 0, 1),
+    (-1, -1, 0), (-1, 1, 0), (1, -1, 0), (1, 1, 0),
+    (-1, 0, -1), (-1, 0, 1), (1, 0, -1), (1, 0, 1),
+    (0, -1, -1), (0, -1, 1), (0, 1, -1), (0, 1, 1),
+    (-1, -1, -1), (-1, -1, 1), (-1, 1, -1), (-1, 1, 1),
+    (1, -1, -1), (1, -1, 1), (1, 1, -1), (1, 1, 1),
+];
+
+impl Connectivity {
+    // Offsets to each neighbour, faces first.
+    pub fn offsets(&self) -> &'static [Voxel] {
+        match self {
+            Connectivity::Faces => &OFFSETS[..6],
+            Connectivity::Edges => &OFFSETS[..18],
+            Connectivity::Corners => &OFFSETS,
+        }
+    }
+}
+
+pub fn neighbours((x, y, z): Voxel, connectivity: Connectivity) -> impl Iterator<Item = Voxel> {
+    connectivity.offsets().iter().map(move |(dx, dy, dz)| (x + dx, y + dy, z + dz))
+}
+
+// The smallest box, inclusive at both corners, holding a set of voxels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoundingBox {
+    pub min: Voxel,
+    pub max: Voxel,
+}
+
+impl BoundingBox {
+    pub fn contains(&self, (x, y, z): Voxel) -> bool {
+        (self.min.0..=self.max.0).contains(&x)
+            && (self.min.1..=self.max.1).contains(&y)
+            && (self.min.2..=self.max.2).contains(&z)
+    }
+
+    // Grown by margin on every side.
+    pub fn padded(&self, margin: isize) -> BoundingBox {
+        BoundingBox {
+            min: (self.min.0 - margin, self.min.1 - margin, self.min.2 - margin),
+            max: (self.max.0 + margin, self.max.1 + margin, self.max.2 + margin),
+        }
+    }
+
+    pub fn voxels(&self) -> impl Iterator<Item = Voxel> {
+        let (min, max) = (self.min, self.max);
+        (min.0..=max.0).flat_map(move |x| (min.1..=max.1).flat_map(move |y| (min.2..=max.2).map(move |z| (x, y, z))))
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VoxelSet {
+    voxels: HashSet<Voxel>,
+}
+
+impl VoxelSet {
+    pub fn new() -> VoxelSet {
+        VoxelSet { voxels: HashSet::new() }
+    }
+
+    pub fn insert(&mut self, voxel: Voxel) -> bool {
+        self.voxels.insert(voxel)
+    }
+
+    pub fn contains(&self, voxel: Voxel) -> bool {
+        self.voxels.contains(&voxel)
+    }
+
+    pub fn len(&self) -> usize {
+        self.voxels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.voxels.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Voxel> {
+        self.voxels.iter()
+    }
+
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        let mut voxels = self.voxels.iter();
+        let first = *voxels.next()?;
+        let bbox = voxels.fold(BoundingBox { min: first, max: first }, |bbox, &(x, y, z)| BoundingBox {
+            min: (bbox.min.0.min(x), bbox.min.1.min(y), bbox.min.2.min(z)),
+            max: (bbox.max.0.max(x), bbox.max.1.max(y), bbox.max.2.max(z)),
+        });
+
+        Some(bbox)
+    }
+
+    // Everything reachable from start through voxels that pass, by connectivity.
+    fn flood<F>(start: Voxel, connectivity: Connectivity, passable: F) -> VoxelSet
+        where F: Fn(Voxel) -> bool
+    {
+        let mut reached = VoxelSet::new();
+        let mut to_visit: VecDeque<Voxel> = VecDeque::new();
+        reached.insert(start);
+        to_visit.push_back(start);
+        while let Some(voxel) = to_visit.pop_front() {
+            for next in neighbours(voxel, connectivity) {
+                if passable(next) && reached.insert(next) {
+                    to_visit.push_back(next);
+                }
+            }
+        }
+
+        reached
+    }
+
+    // The set split into pieces that are connected to each other.
+    pub fn components(&self, connectivity: Connectivity) -> Vec<VoxelSet> {
+        let mut components: Vec<VoxelSet> = Vec::new();
+        let mut seen: HashSet<Voxel> = HashSet::new();
+        for &voxel in &self.voxels {
+            if seen.contains(&voxel) {
+                continue;
+            }
+            let component = VoxelSet::flood(voxel, connectivity, |v| self.contains(v));
+            seen.extend(component.iter());
+            components.push(component);
+        }
+
+        components
+    }
+
+    // The empty voxels that can be reached from outside, within the bounding
+    // box padded by one so that the outside is all one piece.
+    pub fn exterior(&self) -> VoxelSet {
+        let bbox = match self.bounding_box() {
+            Some(bbox) => bbox.padded(1),
+            None => return VoxelSet::new(),
+        };
+
+        VoxelSet::flood(bbox.min, Connectivity::Faces, |v| bbox.contains(v) && !self.contains(v))
+    }
+
+    // The enclosed pockets of empty voxels, each as a set.
+    pub fn cavities(&self) -> Vec<VoxelSet> {
+        let bbox = match self.bounding_box() {
+            Some(bbox) => bbox,
+            None => return Vec::new(),
+        };
+        let exterior = self.exterior();
+        let enclosed: VoxelSet = bbox.voxels()
+            .filter(|&v| !self.contains(v) && !exterior.contains(v))
+            .collect();
+
+        enclosed.components(Connectivity::Faces)
+    }
+
+    // Number of faces this set's voxels share with other's.
+    pub fn faces_touching(&self, other: &VoxelSet) -> usize {
+        self.voxels.iter()
+            .map(|&voxel| neighbours(voxel, Connectivity::Faces).filter(|&n| other.contains(n)).count())
+            .sum()
+    }
+
+    // Faces not shared with another voxel of the set, including those
+    // facing into cavities.
+    pub fn surface_area(&self) -> usize {
+        6 * self.len() - self.faces_touching(self)
+    }
+
+    // Faces that can be reached from outside.
+    pub fn exterior_area(&self) -> usize {
+        self.faces_touching(&self.exterior())
+    }
+}
+
+impl FromIterator<Voxel> for VoxelSet {
+    fn from_iter<I: IntoIterator<Item = Voxel>>(iter: I) -> VoxelSet {
+        VoxelSet { voxels: iter.into_iter().collect() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A cube of side 3 with its middle missing.
+    fn hollow_cube() -> VoxelSet {
+        let bbox = BoundingBox { min: (0, 0, 0), max: (2, 2, 2) };
+        bbox.voxels().filter(|&v| v != (1, 1, 1)).collect()
+    }
+
+    #[test]
+    fn test_connectivity() {
+        assert_eq!(Connectivity::Faces.offsets().len(), 6);
+        assert_eq!(Connectivity::Edges.offsets().len(), 18);
+        assert_eq!(Connectivity::Corners.offsets().len(), 26);
+        assert_eq!(Connectivity::Corners.offsets()[..6], Connectivity::Faces.offsets()[..]);
+        for (connectivity, axes) in [(Connectivity::Faces, 1), (Connectivity::Edges, 2), (Connectivity::Corners, 3)] {
+            let offsets: HashSet<Voxel> = connectivity.offsets().iter().copied().collect();
+            assert_eq!(offsets.len(), connectivity.offsets().len());
+            assert!(offsets.iter().all(|&(dx, dy, dz)| {
+                let moved = [dx, dy, dz].iter().filter(|d| **d != 0).count();
+                [dx, dy, dz].iter().all(|d| d.abs() <= 1) && (1..=axes).contains(&moved)
+            }));
+        }
+        assert!(neighbours((5, 5, 5), Connectivity::Faces).all(|(x, y, z)| (x - 5).abs() + (y - 5).abs() + (z - 5).abs() == 1));
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        assert_eq!(VoxelSet::new().bounding_box(), None);
+        let set: VoxelSet = [(1, -2, 3), (0, 4, 3), (2, 0, -1)].into_iter().collect();
+        let bbox = set.bounding_box().unwrap();
+        assert_eq!(bbox, BoundingBox { min: (0, -2, -1), max: (2, 4, 3) });
+        assert_eq!(bbox.voxels().count(), 3 * 7 * 5);
+        assert!(bbox.contains((1, 1, 1)));
+        assert!(!bbox.contains((3, 1, 1)));
+        assert!(bbox.padded(1).contains((3, 1, 1)));
+    }
+
+    #[test]
+    fn test_components() {
+        // Two pairs touching along an edge, and a loner touching them only at a corner.
+        let set: VoxelSet = [(0, 0, 0), (1, 0, 0), (2, 1, 0), (3, 1, 0), (4, 2, 1)].into_iter().collect();
+        assert_eq!(set.components(Connectivity::Faces).len(), 3);
+        assert_eq!(set.components(Connectivity::Edges).len(), 2);
+        assert_eq!(set.components(Connectivity::Corners).len(), 1);
+        let sizes: usize = set.components(Connectivity::Faces).iter().map(|c| c.len()).sum();
+        assert_eq!(sizes, 5);
+    }
+
+    #[test]
+    fn test_areas() {
+        let single: VoxelSet = [(0, 0, 0)].into_iter().collect();
+        assert_eq!(single.surface_area(), 6);
+        assert_eq!(single.exterior_area(), 6);
+        assert_eq!(single.exterior().len(), 27 - 1);
+        assert!(single.cavities().is_empty());
+
+        let pair: VoxelSet = [(0, 0, 0), (0, 0, 1)].into_iter().collect();
+        assert_eq!(pair.surface_area(), 10);
+
+        let hollow = hollow_cube();
+        assert_eq!(hollow.surface_area(), 54 + 6);
+        assert_eq!(hollow.exterior_area(), 54);
+        let cavities = hollow.cavities();
+        assert_eq!(cavities.len(), 1);
+        assert!(cavities[0].contains((1, 1, 1)));
+        assert_eq!(hollow.faces_touching(&cavities[0]), 6);
+    }
+}